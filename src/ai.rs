@@ -0,0 +1,208 @@
+use ggez::mint::Point2;
+use rand::Rng;
+
+use crate::ball::Ball;
+use crate::paddle::Paddle;
+
+/// Most wall bounces traced by `predict_ball_path`, guarding against near-vertical balls
+const MAX_PREDICTED_BOUNCES: usize = 32;
+
+/// A deliberate misjudgement misses the ball by up to this much more than it takes to miss at all
+const MAX_MISS_MARGIN: f32 = 40.0;
+
+/// Computer control for a paddle. It moves no faster than a human could.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ai {
+    /// Height the AI is steering the paddle's center towards
+    pub target_y: f32,
+    /// How far off the AI is aiming for the current return
+    pub miss_offset: f32,
+    /// Whether the ball was heading towards the paddle last frame
+    pub incoming: bool,
+    /// Furthest the AI moves its paddle in a frame, the same as a player's paddle speed
+    pub speed: f32,
+}
+
+impl Ai {
+    /// Creates an AI that starts out holding `paddle` where it is, moving it at up to `speed`.
+    pub fn new(paddle: &Paddle, speed: f32) -> Ai {
+        Ai {
+            target_y: paddle.center_y(),
+            miss_offset: 0.,
+            incoming: false,
+            speed,
+        }
+    }
+
+    /// Aims for where the ball will reach the paddle's face and moves the paddle one step
+    /// towards it. The AI plays the right paddle, so its face is the paddle's left edge. A ball heading away leaves the current target in place.
+    /// Each time the ball turns towards the paddle, there's a `miss_chance` the AI misjudges
+    /// that return badly enough to miss it.
+    pub fn update(
+        &mut self,
+        paddle: &mut Paddle,
+        ball: &Ball,
+        radius: f32,
+        y_bound: f32,
+        miss_chance: f32,
+        rng: &mut impl Rng,
+    ) {
+        let landing = predict_ball_landing(ball, paddle.pos.x, radius, y_bound);
+        if landing.is_some() && !self.incoming {
+            self.miss_offset = roll_miss_offset(paddle.height, radius, miss_chance, rng);
+        }
+        self.incoming = landing.is_some();
+
+        if let Some(landing_y) = landing {
+            self.target_y = landing_y + self.miss_offset;
+        }
+
+        let offset = self.target_y - paddle.center_y();
+        paddle.pos.y += offset.clamp(-self.speed, self.speed);
+        paddle.clamp_to(y_bound);
+    }
+}
+
+/// How far off to aim for a return: 0 to play it properly, or, with probability
+/// `miss_chance`, far enough above or below the ball for a paddle of `paddle_height` to miss it.
+pub fn roll_miss_offset(
+    paddle_height: f32,
+    radius: f32,
+    miss_chance: f32,
+    rng: &mut impl Rng,
+) -> f32 {
+    if rng.random::<f32>() >= miss_chance {
+        return 0.;
+    }
+    let offset = paddle_height / 2. + radius + rng.random::<f32>() * MAX_MISS_MARGIN;
+    if rng.random::<bool>() {
+        offset
+    } else {
+        -offset
+    }
+}
+
+/// Where the ball's center will be vertically when it reaches `surface_x`, bouncing off
+/// the top and bottom walls on the way. `None` if it's not heading there.
+pub fn predict_ball_landing(ball: &Ball, surface_x: f32, radius: f32, y_bound: f32) -> Option<f32> {
+    let frames = (surface_x - ball.pos.x) / ball.vel.x;
+    if !frames.is_finite() || frames < 0. {
+        return None;
+    }
+
+    let unbounded_y = ball.pos.y + ball.vel.y * frames;
+
+    // Fold the straight-line path back into the field, one reflection per wall bounce
+    let span = y_bound - 2. * radius;
+    if span <= 0. {
+        return Some(y_bound / 2.);
+    }
+    let mut folded = (unbounded_y - radius).rem_euclid(2. * span);
+    if folded > span {
+        folded = 2. * span - folded;
+    }
+    Some(folded + radius)
+}
+
+/// The ball's path from where it is to `target_x`, as a polyline with a point at each
+/// wall bounce. Empty if the ball isn't heading towards `target_x`.
+pub fn predict_ball_path(
+    ball: &Ball,
+    target_x: f32,
+    radius: f32,
+    y_bound: f32,
+) -> Vec<Point2<f32>> {
+    let frames_to_target = (target_x - ball.pos.x) / ball.vel.x;
+    if !frames_to_target.is_finite() || frames_to_target < 0. || y_bound <= 2. * radius {
+        return Vec::new();
+    }
+
+    let mut path = vec![ball.pos];
+    let mut pos = ball.pos;
+    let mut vel_y = ball.vel.y;
+    let mut frames_left = frames_to_target;
+    for _ in 0..MAX_PREDICTED_BOUNCES {
+        let frames_to_wall = if vel_y > 0. {
+            (y_bound - radius - pos.y) / vel_y
+        } else if vel_y < 0. {
+            (radius - pos.y) / vel_y
+        } else {
+            f32::INFINITY
+        };
+
+        if frames_to_wall >= frames_left {
+            break;
+        }
+
+        let frames = frames_to_wall.max(0.);
+        pos = Point2 {
+            x: pos.x + ball.vel.x * frames,
+            y: pos.y + vel_y * frames,
+        };
+        path.push(pos);
+        vel_y = -vel_y;
+        frames_left -= frames;
+    }
+
+    path.push(Point2 {
+        x: target_x,
+        y: pos.y + vel_y * frames_left,
+    });
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{SeedableRng, rngs::StdRng};
+
+    fn ball(pos: Point2<f32>, vel: Point2<f32>) -> Ball {
+        Ball {
+            pos,
+            vel,
+            last_hitter: None,
+            arc: 0.,
+        }
+    }
+
+    #[test]
+    fn predicted_path_bounces_and_reaches_the_far_wall() {
+        let ball = ball(Point2 { x: 100., y: 300. }, Point2 { x: 5., y: 5. });
+
+        // 58 frames down to the bottom wall, then the remaining 82 heading back up
+        let path = predict_ball_path(&ball, 800., 10., 600.);
+        assert_eq!(
+            path,
+            vec![
+                Point2 { x: 100., y: 300. },
+                Point2 { x: 390., y: 590. },
+                Point2 { x: 800., y: 180. },
+            ]
+        );
+    }
+
+    #[test]
+    fn no_path_for_a_ball_heading_away() {
+        let ball = ball(Point2 { x: 100., y: 300. }, Point2 { x: -5., y: 5. });
+        assert!(predict_ball_path(&ball, 800., 10., 600.).is_empty());
+    }
+
+    #[test]
+    fn miss_chance_decides_whether_the_ai_misjudges() {
+        let incoming = ball(Point2 { x: 400., y: 300. }, Point2 { x: 5., y: 0. });
+        let mut rng = StdRng::seed_from_u64(1);
+
+        for _ in 0..20 {
+            let mut paddle = Paddle::new(770., 10., 100., 600.);
+            let mut ai = Ai::new(&paddle, 5.);
+            ai.update(&mut paddle, &incoming, 10., 600., 0., &mut rng);
+            assert_eq!(ai.miss_offset, 0.);
+            assert_eq!(ai.target_y, 300.);
+
+            let mut paddle = Paddle::new(770., 10., 100., 600.);
+            let mut ai = Ai::new(&paddle, 5.);
+            ai.update(&mut paddle, &incoming, 10., 600., 1., &mut rng);
+            assert!(ai.miss_offset.abs() > paddle.height / 2. + 10.);
+        }
+    }
+}
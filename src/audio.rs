@@ -0,0 +1,131 @@
+use ggez::{
+    Context,
+    audio::{self, SoundSource},
+};
+
+use crate::ball::BALL_SPEED;
+use crate::config::AudioSettings;
+use crate::events::GameEvent;
+
+/// Resource paths of the sounds, all optional
+const BOUNCE_SOUND_PATH: &str = "/bounce.ogg";
+const SCORE_SOUND_PATH: &str = "/score.ogg";
+const MUSIC_PATH: &str = "/music.ogg";
+
+/// How much the bounce pitch rises per unit of ball speed above the default serve speed,
+/// and the range it's kept within
+pub const PITCH_PER_SPEED: f32 = 0.05;
+pub const MIN_PITCH: f32 = 0.8;
+pub const MAX_PITCH: f32 = 1.6;
+
+/// Loaded sound sources.
+pub struct Audio {
+    bounce: Option<audio::Source>,
+    score: Option<audio::Source>,
+    music: Option<audio::Source>,
+}
+
+impl Audio {
+    /// Loads whichever sounds are available. Missing files just mean silence.
+    pub fn new(ctx: &mut Context, settings: &AudioSettings) -> Audio {
+        let mut audio = Audio {
+            bounce: load_source(ctx, BOUNCE_SOUND_PATH),
+            score: load_source(ctx, SCORE_SOUND_PATH),
+            music: load_source(ctx, MUSIC_PATH),
+        };
+
+        if let Some(music) = &mut audio.music {
+            music.set_repeat(true);
+        }
+        audio.apply_settings(ctx, settings);
+
+        audio
+    }
+
+    /// Pushes the current settings to the sources, starting or stopping the music as needed.
+    pub fn apply_settings(&mut self, ctx: &mut Context, settings: &AudioSettings) {
+        let volume = settings.master_volume;
+        for source in [&mut self.bounce, &mut self.score].into_iter().flatten() {
+            source.set_volume(volume);
+        }
+
+        let music_allowed = settings.music_allowed();
+        if let Some(music) = &mut self.music {
+            music.set_volume(volume);
+            if music_allowed && !music.playing() {
+                if let Err(e) = music.play(ctx) {
+                    log::warn!("Could not play music: {e}");
+                }
+            } else if !music_allowed && music.playing() {
+                music.stop(ctx).ok();
+            }
+        }
+    }
+
+    /// Plays the sound effect matching `event`, if any.
+    /// Bounces are pitched by `ball_speed` when the setting is on.
+    pub fn play_event(
+        &mut self,
+        ctx: &mut Context,
+        event: GameEvent,
+        ball_speed: f32,
+        settings: &AudioSettings,
+    ) {
+        if !settings.sfx_allowed() {
+            return;
+        }
+
+        let source = match event {
+            GameEvent::PaddleHit(_) | GameEvent::WallBounce => {
+                if let Some(bounce) = &mut self.bounce {
+                    bounce.set_pitch(if settings.speed_pitch {
+                        pitch_for_speed(ball_speed)
+                    } else {
+                        1.
+                    });
+                }
+                &mut self.bounce
+            }
+            GameEvent::Scored(_) | GameEvent::MatchWon { .. } => &mut self.score,
+        };
+        if let Some(source) = source
+            && let Err(e) = source.play_detached(ctx)
+        {
+            log::warn!("Could not play sound: {e}");
+        }
+    }
+}
+
+/// Playback pitch for a bounce at `speed`: normal at the default serve speed, higher when
+/// faster, and kept between `MIN_PITCH` and `MAX_PITCH`.
+pub fn pitch_for_speed(speed: f32) -> f32 {
+    if !speed.is_finite() {
+        return 1.;
+    }
+    (1. + (speed - BALL_SPEED) * PITCH_PER_SPEED).clamp(MIN_PITCH, MAX_PITCH)
+}
+
+fn load_source(ctx: &mut Context, path: &str) -> Option<audio::Source> {
+    audio::Source::new(ctx, path)
+        .map_err(|e| log::info!("Sound {path} not loaded: {e}"))
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounce_pitch_rises_with_speed_within_its_range() {
+        assert_eq!(pitch_for_speed(BALL_SPEED), 1.);
+        assert!(pitch_for_speed(BALL_SPEED + 4.) > 1.);
+        assert!(pitch_for_speed(BALL_SPEED - 2.) < 1.);
+
+        for speed in [0., 1., 50., 1000., -100.] {
+            let pitch = pitch_for_speed(speed);
+            assert!((MIN_PITCH..=MAX_PITCH).contains(&pitch), "{speed}: {pitch}");
+        }
+        assert_eq!(pitch_for_speed(1000.), MAX_PITCH);
+        assert_eq!(pitch_for_speed(f32::NAN), 1.);
+    }
+}
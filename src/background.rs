@@ -0,0 +1,127 @@
+use ggez::{
+    Context, GameResult,
+    graphics::{self, Color},
+    mint::Point2,
+};
+
+use crate::theme::{BackgroundStyle, Theme};
+
+/// Renders the themed background behind everything else.
+/// The mesh is cached and only rebuilt when the field size or theme changes.
+#[derive(Default)]
+pub struct Background {
+    cached: Option<(f32, f32, Theme, Option<graphics::Mesh>)>,
+}
+
+impl Background {
+    pub fn draw(
+        &mut self,
+        canvas: &mut graphics::Canvas,
+        ctx: &mut Context,
+        theme: &Theme,
+    ) -> GameResult {
+        let (width, height) = ctx.gfx.drawable_size();
+
+        let is_stale = !matches!(
+            &self.cached,
+            Some((w, h, t, _)) if *w == width && *h == height && t == theme
+        );
+        if is_stale {
+            let mesh = build_mesh(ctx, width, height, theme)?;
+            self.cached = Some((width, height, *theme, mesh));
+        }
+
+        if let Some((_, _, _, Some(mesh))) = &self.cached {
+            canvas.draw(mesh, graphics::DrawParam::new());
+        }
+
+        Ok(())
+    }
+}
+
+/// The start and end points of every grid line covering a `width` x `height` field.
+pub fn grid_lines(width: f32, height: f32, spacing: f32) -> Vec<[Point2<f32>; 2]> {
+    if spacing <= 0. {
+        return Vec::new();
+    }
+
+    let vertical = (1..)
+        .map(|i| i as f32 * spacing)
+        .take_while(|x| *x < width)
+        .map(|x| [Point2 { x, y: 0. }, Point2 { x, y: height }]);
+    let horizontal = (1..)
+        .map(|i| i as f32 * spacing)
+        .take_while(|y| *y < height)
+        .map(|y| [Point2 { x: 0., y }, Point2 { x: width, y }]);
+
+    vertical.chain(horizontal).collect()
+}
+
+fn build_mesh(
+    ctx: &mut Context,
+    width: f32,
+    height: f32,
+    theme: &Theme,
+) -> GameResult<Option<graphics::Mesh>> {
+    if width <= 0. || height <= 0. {
+        return Ok(None);
+    }
+
+    match theme.background_style {
+        BackgroundStyle::Solid => Ok(None),
+        BackgroundStyle::Grid { spacing } => {
+            let lines = grid_lines(width, height, spacing);
+            if lines.is_empty() {
+                return Ok(None);
+            }
+
+            let color = Color {
+                a: 0.08,
+                ..theme.foreground
+            };
+            let mut builder = graphics::MeshBuilder::new();
+            for line in &lines {
+                builder.line(line, 1.0, color)?;
+            }
+            Ok(Some(graphics::Mesh::from_data(ctx, builder.build())))
+        }
+        BackgroundStyle::Gradient { bottom } => {
+            let vertex = |x: f32, y: f32, color: Color| graphics::Vertex {
+                position: [x, y],
+                uv: [0., 0.],
+                color: color.into(),
+            };
+            let top = theme.background;
+            let vertices = [
+                vertex(0., 0., top),
+                vertex(width, 0., top),
+                vertex(width, height, bottom),
+                vertex(0., height, bottom),
+            ];
+            let indices = [0, 1, 2, 0, 2, 3];
+            Ok(Some(graphics::Mesh::from_data(
+                ctx,
+                graphics::MeshData {
+                    vertices: &vertices,
+                    indices: &indices,
+                },
+            )))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grid_has_a_line_per_spacing_inside_the_board() {
+        // 800 / 100 leaves 7 interior vertical lines, 600 / 100 leaves 5 horizontal ones
+        let lines = grid_lines(800., 600., 100.);
+        assert_eq!(lines.len(), 12);
+        assert_eq!(lines.iter().filter(|[a, b]| a.x == b.x).count(), 7);
+
+        assert_eq!(grid_lines(850., 650., 100.).len(), 14);
+        assert!(grid_lines(800., 600., 0.).is_empty());
+    }
+}
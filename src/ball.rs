@@ -0,0 +1,375 @@
+use ggez::{graphics::Rect, mint::Point2};
+use rand::Rng;
+
+use crate::side::Side;
+
+/// Constants
+/// Default serve speed, also used when recovering from a degenerate velocity
+pub const BALL_SPEED: f32 = 6.0;
+pub const BALL_RADIUS: f32 = 8.0;
+/// How much the ball grows per paddle hit when ball growth is enabled, and its largest size
+pub const BALL_GROWTH_PER_HIT: f32 = 0.5;
+pub const MAX_BALL_RADIUS: f32 = 20.0;
+/// Smallest share of a serve's speed that must go into horizontal movement
+pub const MIN_SERVE_X_FRACTION: f32 = 0.75;
+/// Steepest a serve can ever be set to leave at, in radians from horizontal:
+/// `acos(MIN_SERVE_X_FRACTION)`, so serves never crawl towards a wall. Also the default.
+pub const SERVE_ANGLE_LIMIT: f32 = 0.722_734_2;
+pub const DEFAULT_MAX_SERVE_ANGLE: f32 = SERVE_ANGLE_LIMIT;
+/// How much the drawn ball stretches along its velocity per unit of speed, and the most it can
+pub const STRETCH_PER_SPEED: f32 = 0.03;
+pub const MAX_STRETCH: f32 = 1.5;
+/// Speed lines trail a ball moving faster than this, growing longer the further past it it is
+pub const SPEED_LINE_THRESHOLD: f32 = 9.0;
+pub const SPEED_LINE_LENGTH_PER_SPEED: f32 = 4.0;
+/// Sideways offsets of the speed lines from the ball's path, as a share of its radius
+pub const SPEED_LINE_OFFSETS: [f32; 3] = [-0.6, 0., 0.6];
+/// Smallest share of the speed kept in horizontal movement after a paddle corner hit
+pub const MIN_CORNER_X_FRACTION: f32 = 0.3;
+/// A mercy ball is served at this share of the normal speed, and pulled downwards by
+/// `MERCY_ARC` every frame
+pub const MERCY_SPEED_FACTOR: f32 = 0.6;
+pub const MERCY_ARC: f32 = 0.04;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Ball {
+    pub pos: Point2<f32>,
+    pub vel: Point2<f32>,
+    /// The paddle that last returned the ball, `None` for a ball nobody has touched since its serve
+    pub last_hitter: Option<Side>,
+    /// Downward pull added to the vertical velocity every frame, making the ball arc
+    pub arc: f32,
+}
+
+impl Ball {
+    /// Creates a ball at the given position with a fresh random serve at `speed`, at most
+    /// `max_angle` from horizontal.
+    pub fn new(pos: Point2<f32>, speed: f32, max_angle: f32, rng: &mut impl Rng) -> Ball {
+        Ball {
+            pos,
+            vel: Self::random_velocity(speed, max_angle, rng),
+            last_hitter: None,
+            arc: 0.,
+        }
+    }
+
+    /// This ball turned into a slower, arcing mercy ball that's easier to return.
+    pub fn into_mercy(mut self) -> Ball {
+        self.set_speed(self.speed() * MERCY_SPEED_FACTOR);
+        self.arc = MERCY_ARC;
+        self
+    }
+
+    /// Whether this is a mercy ball.
+    pub fn is_mercy(&self) -> bool {
+        self.arc != 0.
+    }
+
+    /// Generates a random ball velocity with a random angle
+    /// Avoids angles too close to π/2 to prevent excessive vertical bouncing
+    pub fn random_velocity(speed: f32, max_angle: f32, rng: &mut impl Rng) -> Point2<f32> {
+        let x_direction = if rng.random::<f32>() < 0.5 { 1.0 } else { -1.0 };
+
+        Self::serve_velocity(x_direction, speed, max_angle, rng)
+    }
+
+    /// Generates a serve with a random angle travelling in the given horizontal direction
+    /// (`1.0` towards the right wall, `-1.0` towards the left wall).
+    pub fn serve_velocity(
+        x_direction: f32,
+        speed: f32,
+        max_angle: f32,
+        rng: &mut impl Rng,
+    ) -> Point2<f32> {
+        // Generate angle between -max_angle and max_angle. Capping it at `SERVE_ANGLE_LIMIT`
+        // (computed here exactly) keeps at least `MIN_SERVE_X_FRACTION` of the speed horizontal.
+        let max_angle = max_angle.clamp(0., MIN_SERVE_X_FRACTION.acos());
+        let angle = (rng.random::<f32>() - 0.5) * 2.0 * max_angle;
+
+        Point2 {
+            x: x_direction * angle.cos() * speed,
+            y: angle.sin() * speed,
+        }
+    }
+
+    /// Effective radius of a ball that has been hit `rally_hits` times this rally.
+    pub fn radius_for_rally(rally_hits: u32, growth: bool) -> f32 {
+        if !growth {
+            return BALL_RADIUS;
+        }
+
+        (BALL_RADIUS + rally_hits as f32 * BALL_GROWTH_PER_HIT).min(MAX_BALL_RADIUS)
+    }
+
+    /// A flat serve at the default speed, continuing in the current horizontal direction.
+    /// Deterministic, so recovering from bad physics never disturbs a seeded match.
+    fn safe_serve_velocity(&self) -> Point2<f32> {
+        let x_direction = if self.vel.x < 0. { -1. } else { 1. };
+        Point2 {
+            x: x_direction * BALL_SPEED,
+            y: 0.,
+        }
+    }
+
+    pub fn speed(&self) -> f32 {
+        (self.vel.x * self.vel.x + self.vel.y * self.vel.y).sqrt()
+    }
+
+    /// Returns true if both velocity components are finite numbers.
+    pub fn has_finite_velocity(&self) -> bool {
+        self.vel.x.is_finite() && self.vel.y.is_finite()
+    }
+
+    /// Rescales the velocity to `speed`, keeping its direction.
+    /// If the direction can't be normalized or the target speed is degenerate
+    /// (zero or non-finite), the ball gets a safe serve instead.
+    pub fn set_speed(&mut self, speed: f32) {
+        let current = self.speed();
+        let scale = speed / current;
+
+        if current == 0. || !scale.is_finite() || scale <= 0. {
+            log::warn!(
+                "Ball velocity could not be rescaled ({:?}), re-serving",
+                self.vel
+            );
+            self.vel = self.safe_serve_velocity();
+            return;
+        }
+
+        self.vel.x *= scale;
+        self.vel.y *= scale;
+    }
+
+    /// How much longer than wide the ball is drawn when moving at `speed`.
+    /// Purely cosmetic, collisions always use the true radius.
+    pub fn stretch_factor(speed: f32) -> f32 {
+        if !speed.is_finite() {
+            return 1.;
+        }
+        (1. + speed.abs() * STRETCH_PER_SPEED).min(MAX_STRETCH)
+    }
+
+    /// Motion lines trailing the ball, as start and end points, when it's moving faster than
+    /// `SPEED_LINE_THRESHOLD`. Each starts at the ball's back edge and points away from where it's
+    /// heading. Purely cosmetic.
+    pub fn speed_lines(&self, radius: f32) -> Vec<[Point2<f32>; 2]> {
+        let speed = self.speed();
+        if !speed.is_finite() || speed <= SPEED_LINE_THRESHOLD {
+            return Vec::new();
+        }
+
+        let back = Point2 {
+            x: -self.vel.x / speed,
+            y: -self.vel.y / speed,
+        };
+        let side = Point2 {
+            x: -back.y,
+            y: back.x,
+        };
+        let length = (speed - SPEED_LINE_THRESHOLD) * SPEED_LINE_LENGTH_PER_SPEED;
+        SPEED_LINE_OFFSETS
+            .iter()
+            .map(|offset| {
+                let start = Point2 {
+                    x: self.pos.x + back.x * radius + side.x * offset * radius,
+                    y: self.pos.y + back.y * radius + side.y * offset * radius,
+                };
+                let end = Point2 {
+                    x: start.x + back.x * length,
+                    y: start.y + back.y * length,
+                };
+                [start, end]
+            })
+            .collect()
+    }
+
+    /// The box bounding the ball when it has the given radius.
+    pub fn aabb(&self, radius: f32) -> Rect {
+        Rect::new(
+            self.pos.x - radius,
+            self.pos.y - radius,
+            radius * 2.,
+            radius * 2.,
+        )
+    }
+
+    /// Whether the ball's center is inside a `width` x `height` field grown by `margin`
+    /// on every side. A non-finite position is never inside.
+    pub fn is_within(&self, width: f32, height: f32, margin: f32) -> bool {
+        (-margin..=width + margin).contains(&self.pos.x)
+            && (-margin..=height + margin).contains(&self.pos.y)
+    }
+
+    /// Bounces the ball off a paddle corner, reflecting its velocity about the line from
+    /// `corner` to the ball's center. The ball always leaves moving in `away_x`'s direction
+    /// and with enough horizontal speed to not skim along the paddle.
+    pub fn bounce_off_corner(&mut self, corner: Point2<f32>, away_x: f32) {
+        let speed = self.speed();
+        let (dx, dy) = (self.pos.x - corner.x, self.pos.y - corner.y);
+        let distance = (dx * dx + dy * dy).sqrt();
+
+        if distance > 0. {
+            let normal = Point2 {
+                x: dx / distance,
+                y: dy / distance,
+            };
+            let dot = self.vel.x * normal.x + self.vel.y * normal.y;
+            // Only a ball moving into the corner gets reflected
+            if dot < 0. {
+                self.vel.x -= 2. * dot * normal.x;
+                self.vel.y -= 2. * dot * normal.y;
+            }
+        }
+
+        let x_speed = self.vel.x.abs().max(speed * MIN_CORNER_X_FRACTION);
+        let y_speed = (speed * speed - x_speed * x_speed).max(0.).sqrt();
+        self.vel = Point2 {
+            x: away_x.signum() * x_speed,
+            y: y_speed.copysign(self.vel.y),
+        };
+    }
+
+    /// Replaces a non-finite velocity with a safe serve.
+    /// Returns true if a recovery was needed.
+    pub fn recover_if_non_finite(&mut self) -> bool {
+        if self.has_finite_velocity() {
+            return false;
+        }
+
+        log::warn!(
+            "Ball velocity became non-finite ({:?}), re-serving",
+            self.vel
+        );
+        self.vel = self.safe_serve_velocity();
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{SeedableRng, rngs::StdRng};
+
+    fn ball_with_velocity(x: f32, y: f32) -> Ball {
+        Ball {
+            pos: Point2 { x: 100., y: 100. },
+            vel: Point2 { x, y },
+            last_hitter: None,
+            arc: 0.,
+        }
+    }
+
+    #[test]
+    fn rescaling_a_stopped_ball_recovers_a_serve() {
+        let mut ball = ball_with_velocity(0., 0.);
+        ball.set_speed(BALL_SPEED);
+
+        assert!(ball.has_finite_velocity());
+        assert!((ball.speed() - BALL_SPEED).abs() < 1e-4);
+    }
+
+    #[test]
+    fn non_finite_velocity_is_replaced() {
+        let mut ball = ball_with_velocity(f32::NAN, f32::INFINITY);
+
+        assert!(ball.recover_if_non_finite());
+        assert!(ball.has_finite_velocity());
+        assert!(ball.vel.x.abs() > 0.);
+        assert!(!ball.recover_if_non_finite());
+    }
+
+    #[test]
+    fn serves_keep_a_horizontal_floor() {
+        let mut rng = StdRng::seed_from_u64(3);
+        for max_angle in [DEFAULT_MAX_SERVE_ANGLE, std::f32::consts::PI] {
+            for _ in 0..1000 {
+                let vel = Ball::random_velocity(BALL_SPEED, max_angle, &mut rng);
+                assert!(vel.x.abs() >= MIN_SERVE_X_FRACTION * BALL_SPEED - 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn corner_hit_bounces_outward_off_the_corner() {
+        // Coming down onto the top corner of a left paddle's face, up and to the right of it
+        let mut ball = ball_with_velocity(-5., 3.);
+        ball.pos = Point2 { x: 104., y: 97. };
+        let speed = ball.speed();
+
+        ball.bounce_off_corner(Point2 { x: 100., y: 100. }, 1.);
+        assert!(ball.vel.x > 0.);
+        assert!(ball.vel.y < 0.);
+        assert!((ball.speed() - speed).abs() < 1e-4);
+    }
+
+    #[test]
+    fn corner_bounce_keeps_some_horizontal_speed() {
+        // Dropping straight onto the corner would otherwise leave it moving vertically
+        let mut ball = ball_with_velocity(0., 5.);
+        ball.pos = Point2 { x: 100., y: 95. };
+
+        ball.bounce_off_corner(Point2 { x: 100., y: 100. }, -1.);
+        assert!(ball.vel.x <= -ball.speed() * MIN_CORNER_X_FRACTION + 1e-4);
+        assert!((ball.speed() - 5.).abs() < 1e-4);
+    }
+
+    #[test]
+    fn stretch_grows_with_speed_up_to_a_cap() {
+        let ball = ball_with_velocity(3., 4.);
+        assert_eq!(Ball::stretch_factor(0.), 1.);
+        assert!((Ball::stretch_factor(ball.speed()) - (1. + 5. * STRETCH_PER_SPEED)).abs() < 1e-6);
+        assert_eq!(Ball::stretch_factor(-10.), Ball::stretch_factor(10.));
+        assert_eq!(Ball::stretch_factor(1000.), MAX_STRETCH);
+        assert_eq!(Ball::stretch_factor(f32::NAN), 1.);
+    }
+
+    #[test]
+    fn speed_lines_trail_only_a_fast_ball() {
+        assert!(
+            ball_with_velocity(SPEED_LINE_THRESHOLD, 0.)
+                .speed_lines(10.)
+                .is_empty()
+        );
+
+        // Two units past the threshold, moving right from (100, 100)
+        let lines = ball_with_velocity(SPEED_LINE_THRESHOLD + 2., 0.).speed_lines(10.);
+        assert_eq!(lines.len(), SPEED_LINE_OFFSETS.len());
+        let [start, end] = lines[1];
+        assert_eq!(start, Point2 { x: 90., y: 100. });
+        assert_eq!(
+            end,
+            Point2 {
+                x: 90. - 2. * SPEED_LINE_LENGTH_PER_SPEED,
+                y: 100.
+            }
+        );
+        for [start, end] in lines {
+            assert!(end.x < start.x && end.y == start.y);
+        }
+    }
+
+    #[test]
+    fn mercy_ball_is_slower_and_arcs() {
+        let ball = ball_with_velocity(6., 8.);
+        assert!(!ball.is_mercy());
+
+        let mercy = ball.into_mercy();
+        assert!(mercy.is_mercy());
+        assert_eq!(mercy.arc, MERCY_ARC);
+        assert!((mercy.speed() - 10. * MERCY_SPEED_FACTOR).abs() < 1e-4);
+    }
+
+    #[test]
+    fn serves_stay_within_the_configured_angle() {
+        let mut rng = StdRng::seed_from_u64(5);
+        for max_angle in [0., 0.3, DEFAULT_MAX_SERVE_ANGLE] {
+            for x_direction in [-1., 1.] {
+                for _ in 0..500 {
+                    let vel = Ball::serve_velocity(x_direction, BALL_SPEED, max_angle, &mut rng);
+                    assert_eq!(vel.x.signum(), x_direction);
+                    assert!(vel.y.atan2(vel.x.abs()).abs() <= max_angle + 1e-4);
+                }
+            }
+        }
+    }
+}
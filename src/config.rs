@@ -0,0 +1,345 @@
+use std::path::Path;
+use std::time::Duration;
+
+use ggez::conf::WindowSetup;
+use serde::{Deserialize, Serialize};
+
+use crate::settings::{GameSettings, GameSettingsBuilder};
+use crate::theme::CustomTheme;
+
+/// Where the config file lives, relative to the working directory
+pub const CONFIG_PATH: &str = "config.toml";
+
+/// Sound preferences.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioSettings {
+    pub sfx_enabled: bool,
+    pub music_enabled: bool,
+    /// Volume applied to every sound, from 0.0 to 1.0
+    pub master_volume: f32,
+    /// Raise the bounce sound's pitch the faster the ball is going
+    pub speed_pitch: bool,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        AudioSettings {
+            sfx_enabled: true,
+            music_enabled: true,
+            master_volume: 0.8,
+            speed_pitch: true,
+        }
+    }
+}
+
+impl AudioSettings {
+    /// Step used when adjusting the volume from the settings screen
+    pub const VOLUME_STEP: f32 = 0.1;
+
+    #[cfg(feature = "audio")]
+    pub fn sfx_allowed(&self) -> bool {
+        self.sfx_enabled && self.master_volume > 0.
+    }
+
+    #[cfg(feature = "audio")]
+    pub fn music_allowed(&self) -> bool {
+        self.music_enabled && self.master_volume > 0.
+    }
+
+    pub fn adjust_volume(&mut self, steps: f32) {
+        self.master_volume = (self.master_volume + steps * Self::VOLUME_STEP).clamp(0., 1.);
+    }
+}
+
+/// Rendering preferences, which mostly depend on the machine running the game.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DisplaySettings {
+    /// How far (in pixels) the ball mesh may deviate from a true circle.
+    /// Lower values give a rounder ball at the cost of more vertices.
+    pub ball_smoothness: f32,
+    /// Stretch the ball along its direction of travel, more so the faster it goes
+    pub ball_stretch: bool,
+    /// Draw motion lines behind a fast ball
+    pub speed_lines: bool,
+    /// Wait for the display's refresh before showing each frame
+    pub vsync: bool,
+    /// Upper limit on frames per second, or `None` to run as fast as possible
+    pub frame_cap: Option<u32>,
+    /// Window title
+    pub title: String,
+    /// Window icon, a PNG path inside the resources directory such as `/icon.png`
+    pub icon: Option<String>,
+    /// Compact panel with the score, server, rally and ball speed, laid out for stream overlays
+    pub spectator_hud: bool,
+    /// TTF font for the score, menus and messages, a path inside the resources directory
+    pub font: Option<String>,
+}
+
+impl Default for DisplaySettings {
+    fn default() -> Self {
+        DisplaySettings {
+            ball_smoothness: 3.0,
+            ball_stretch: true,
+            speed_lines: true,
+            vsync: true,
+            frame_cap: None,
+            title: "Rusty Pong".to_string(),
+            icon: None,
+            spectator_hud: false,
+            font: None,
+        }
+    }
+}
+
+impl DisplaySettings {
+    /// Smallest tolerance accepted by the mesh builder without producing huge meshes
+    const MIN_BALL_SMOOTHNESS: f32 = 0.05;
+
+    /// Range of frame caps accepted, anything outside is clamped into it
+    pub const MIN_FRAME_CAP: u32 = 20;
+    pub const MAX_FRAME_CAP: u32 = 1000;
+
+    /// The circle tolerance to build the ball mesh with, guarded against unusable values.
+    pub fn ball_tolerance(&self) -> f32 {
+        if self.ball_smoothness.is_finite() {
+            self.ball_smoothness.max(Self::MIN_BALL_SMOOTHNESS)
+        } else {
+            DisplaySettings::default().ball_smoothness
+        }
+    }
+
+    /// The window setup to create the game's window with.
+    /// The icon is left out: it's applied once the window exists, so a bad one isn't fatal.
+    pub fn window_setup(&self) -> WindowSetup {
+        WindowSetup::default().title(&self.title).vsync(self.vsync)
+    }
+
+    /// The resources path of the custom font, or `None` to keep the default one.
+    /// Paths are rooted at the resources directory, so a missing leading `/` is added.
+    pub fn font_path(&self) -> Option<String> {
+        let path = self.font.as_deref()?.trim();
+        if path.is_empty() {
+            None
+        } else if path.starts_with('/') {
+            Some(path.to_string())
+        } else {
+            Some(format!("/{path}"))
+        }
+    }
+
+    /// The shortest a frame may take under the frame cap, if there is one.
+    pub fn min_frame_time(&self) -> Option<Duration> {
+        let cap = self
+            .frame_cap?
+            .clamp(Self::MIN_FRAME_CAP, Self::MAX_FRAME_CAP);
+        Some(Duration::from_secs(1) / cap)
+    }
+}
+
+/// Gameplay preferences from the settings screen that carry over between sessions.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameplayConfig {
+    /// Distance between each paddle and its wall
+    pub paddle_margin: f32,
+    /// Paddle heights, independent per side for handicap play
+    pub left_paddle_height: f32,
+    pub right_paddle_height: f32,
+    /// Whether paddles can be repositioned during the post-score delay
+    pub move_during_delay: bool,
+    /// The ball grows with every paddle hit in a rally
+    pub ball_growth: bool,
+}
+
+impl Default for GameplayConfig {
+    fn default() -> Self {
+        GameplayConfig::from_settings(&GameSettings::default())
+    }
+}
+
+impl GameplayConfig {
+    /// The preferences held by `settings`.
+    pub fn from_settings(settings: &GameSettings) -> GameplayConfig {
+        GameplayConfig {
+            paddle_margin: settings.paddle_margin,
+            left_paddle_height: settings.left_paddle_height,
+            right_paddle_height: settings.right_paddle_height,
+            move_during_delay: settings.move_during_delay,
+            ball_growth: settings.ball_growth,
+        }
+    }
+
+    /// Copies the preferences into `settings`, keeping the old ones if they aren't valid.
+    pub fn apply(&self, settings: &mut GameSettings) {
+        let changed = GameSettingsBuilder::from(settings.clone())
+            .paddle_margin(self.paddle_margin)
+            .left_paddle_height(self.left_paddle_height)
+            .right_paddle_height(self.right_paddle_height)
+            .move_during_delay(self.move_during_delay)
+            .ball_growth(self.ball_growth)
+            .build();
+        match changed {
+            Ok(changed) => *settings = changed,
+            Err(e) => log::warn!("Invalid gameplay config, using defaults: {e}"),
+        }
+    }
+}
+
+/// User preferences persisted between sessions.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub audio: AudioSettings,
+    pub display: DisplaySettings,
+    pub gameplay: GameplayConfig,
+    /// Colors from the color picker, used in place of the default theme's
+    pub custom_theme: Option<CustomTheme>,
+}
+
+impl Config {
+    /// Loads the config from `path`, falling back to defaults if it's missing or invalid.
+    pub fn load(path: impl AsRef<Path>) -> Config {
+        let path = path.as_ref();
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Config::default(),
+            Err(e) => {
+                log::warn!("Could not read config from {}: {e}", path.display());
+                return Config::default();
+            }
+        };
+
+        toml::from_str(&contents).unwrap_or_else(|e| {
+            log::warn!("Invalid config in {}, using defaults: {e}", path.display());
+            Config::default()
+        })
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let contents = toml::to_string(self).map_err(std::io::Error::other)?;
+        std::fs::write(path, contents)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "audio")]
+    #[test]
+    fn disabled_or_silent_audio_is_not_played() {
+        let mut audio = AudioSettings::default();
+        assert!(audio.sfx_allowed() && audio.music_allowed());
+
+        audio.sfx_enabled = false;
+        assert!(!audio.sfx_allowed());
+        assert!(audio.music_allowed());
+
+        audio.sfx_enabled = true;
+        audio.master_volume = 0.;
+        assert!(!audio.sfx_allowed());
+        assert!(!audio.music_allowed());
+    }
+
+    #[test]
+    fn audio_settings_round_trip_through_the_config() {
+        let mut config = Config::default();
+        config.audio.music_enabled = false;
+        config.audio.adjust_volume(-3.);
+
+        let saved = toml::to_string(&config).unwrap();
+        let loaded: Config = toml::from_str(&saved).unwrap();
+        assert_eq!(loaded.audio, config.audio);
+        assert!((loaded.audio.master_volume - 0.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn ball_smoothness_becomes_the_mesh_tolerance() {
+        let mut display = DisplaySettings {
+            ball_smoothness: 0.5,
+            ..DisplaySettings::default()
+        };
+        assert_eq!(display.ball_tolerance(), 0.5);
+
+        display.ball_smoothness = 0.;
+        assert_eq!(
+            display.ball_tolerance(),
+            DisplaySettings::MIN_BALL_SMOOTHNESS
+        );
+
+        display.ball_smoothness = f32::NAN;
+        assert_eq!(
+            display.ball_tolerance(),
+            DisplaySettings::default().ball_smoothness
+        );
+    }
+
+    #[test]
+    fn display_config_parses_into_the_window_setup() {
+        let config: Config = toml::from_str(
+            "[display]\n\
+             vsync = false\n\
+             frame_cap = 144\n\
+             title = \"Pong night\"\n",
+        )
+        .unwrap();
+
+        let setup = config.display.window_setup();
+        assert!(!setup.vsync);
+        assert_eq!(setup.title, "Pong night");
+        assert_eq!(
+            config.display.min_frame_time(),
+            Some(Duration::from_secs(1) / 144)
+        );
+    }
+
+    #[test]
+    fn frame_cap_is_kept_in_range() {
+        let capped = |frame_cap| DisplaySettings {
+            frame_cap,
+            ..DisplaySettings::default()
+        };
+        assert_eq!(capped(None).min_frame_time(), None);
+        assert_eq!(
+            capped(Some(0)).min_frame_time(),
+            Some(Duration::from_secs(1) / DisplaySettings::MIN_FRAME_CAP)
+        );
+        assert_eq!(
+            capped(Some(u32::MAX)).min_frame_time(),
+            Some(Duration::from_secs(1) / DisplaySettings::MAX_FRAME_CAP)
+        );
+    }
+
+    #[test]
+    fn configured_title_is_the_window_title() {
+        assert_eq!(Config::default().display.window_setup().title, "Rusty Pong");
+
+        let config: Config =
+            toml::from_str("[display]\ntitle = \"Office pong\"\nicon = \"/missing.png\"\n")
+                .unwrap();
+        let setup = config.display.window_setup();
+        assert_eq!(setup.title, "Office pong");
+        // The icon is applied once the window exists, so a missing one can't stop it opening
+        assert!(setup.icon.is_empty());
+    }
+
+    #[test]
+    fn font_path_is_rooted_at_the_resources_directory() {
+        let with_font = |font: Option<&str>| DisplaySettings {
+            font: font.map(str::to_string),
+            ..DisplaySettings::default()
+        };
+        assert_eq!(
+            with_font(Some("/arcade.ttf")).font_path().as_deref(),
+            Some("/arcade.ttf")
+        );
+        assert_eq!(
+            with_font(Some(" arcade.ttf ")).font_path().as_deref(),
+            Some("/arcade.ttf")
+        );
+        assert_eq!(with_font(Some("  ")).font_path(), None);
+        assert_eq!(with_font(None).font_path(), None);
+    }
+}
@@ -0,0 +1,91 @@
+use std::time::Duration;
+
+/// Average round trips up to these count as good or fair, anything slower is poor
+pub const GOOD_RTT: Duration = Duration::from_millis(60);
+pub const FAIR_RTT: Duration = Duration::from_millis(150);
+
+/// How healthy a network connection looks, bucketed from recent ping round trips.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionQuality {
+    Good,
+    Fair,
+    Poor,
+    /// No round trips completed recently, shown as "reconnecting…"
+    Stalled,
+}
+
+impl ConnectionQuality {
+    /// Buckets the average of the recent round-trip samples.
+    /// No samples at all means the pings have stalled.
+    pub fn from_rtts(rtts: &[Duration]) -> ConnectionQuality {
+        if rtts.is_empty() {
+            return ConnectionQuality::Stalled;
+        }
+
+        let total: f64 = rtts.iter().map(Duration::as_secs_f64).sum();
+        let average = total / rtts.len() as f64;
+
+        if average <= GOOD_RTT.as_secs_f64() {
+            ConnectionQuality::Good
+        } else if average <= FAIR_RTT.as_secs_f64() {
+            ConnectionQuality::Fair
+        } else {
+            ConnectionQuality::Poor
+        }
+    }
+
+    /// Lit bars on the corner indicator, out of 3.
+    pub fn bars(&self) -> u8 {
+        match self {
+            ConnectionQuality::Good => 3,
+            ConnectionQuality::Fair => 2,
+            ConnectionQuality::Poor => 1,
+            ConnectionQuality::Stalled => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn millis(samples: &[u64]) -> Vec<Duration> {
+        samples.iter().copied().map(Duration::from_millis).collect()
+    }
+
+    #[test]
+    fn average_round_trip_picks_the_bucket() {
+        assert_eq!(
+            ConnectionQuality::from_rtts(&millis(&[20, 40, 60])),
+            ConnectionQuality::Good
+        );
+        // 40ms and 200ms average out to 120ms
+        assert_eq!(
+            ConnectionQuality::from_rtts(&millis(&[40, 200])),
+            ConnectionQuality::Fair
+        );
+        assert_eq!(
+            ConnectionQuality::from_rtts(&millis(&[151])),
+            ConnectionQuality::Poor
+        );
+        assert_eq!(
+            ConnectionQuality::from_rtts(&[]),
+            ConnectionQuality::Stalled
+        );
+    }
+
+    #[test]
+    fn worse_connections_light_fewer_bars() {
+        let bars: Vec<u8> = [
+            ConnectionQuality::Good,
+            ConnectionQuality::Fair,
+            ConnectionQuality::Poor,
+            ConnectionQuality::Stalled,
+        ]
+        .iter()
+        .map(ConnectionQuality::bars)
+        .collect();
+
+        assert_eq!(bars, [3, 2, 1, 0]);
+    }
+}
@@ -0,0 +1,197 @@
+use std::time::Duration;
+
+use crate::settings::GameSettings;
+
+/// How many lines of output the console keeps
+pub const CONSOLE_HISTORY: usize = 8;
+/// Text size of the console overlay
+pub const CONSOLE_TEXT_SCALE: f32 = 18.0;
+/// Space between the console's text and the edge of its panel
+pub const CONSOLE_PADDING: f32 = 8.0;
+
+/// A command typed into the developer console.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    /// `set <setting> <value>`
+    Set { setting: String, value: String },
+    /// `help`
+    Help,
+}
+
+/// Parses one line of console input.
+pub fn parse_command(line: &str) -> Result<Command, String> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    match words.as_slice() {
+        ["set", setting, value] => Ok(Command::Set {
+            setting: setting.to_string(),
+            value: value.to_string(),
+        }),
+        ["set", ..] => Err("usage: set <setting> <value>".to_string()),
+        ["help"] => Ok(Command::Help),
+        [] => Err("type a command, or help".to_string()),
+        [other, ..] => Err(format!("unknown command {other}")),
+    }
+}
+
+/// Names `set` accepts, for the help text
+pub const SETTABLE: [&str; 16] = [
+    "ball_speed",
+    "paddle_margin",
+    "left_paddle_height",
+    "right_paddle_height",
+    "ball_growth",
+    "move_during_delay",
+    "restitution",
+    "ai_miss_chance",
+    "starting_lives",
+    "max_balls",
+    "elimination",
+    "zen",
+    "multi_ball",
+    "doubles",
+    "winning_score",
+    "serve_delay",
+];
+
+/// Sets `setting` to `value`, keeping the old settings if the result wouldn't be valid.
+/// Returns the line to echo back either way.
+pub fn apply_set(
+    settings: &mut GameSettings,
+    setting: &str,
+    value: &str,
+) -> Result<String, String> {
+    fn parse<T: std::str::FromStr>(value: &str) -> Result<T, String> {
+        value
+            .parse()
+            .map_err(|_| format!("{value} is not a valid value"))
+    }
+
+    let mut changed = settings.clone();
+    match setting {
+        "ball_speed" => changed.ball_speed = parse(value)?,
+        "paddle_margin" => changed.paddle_margin = parse(value)?,
+        "left_paddle_height" => changed.left_paddle_height = parse(value)?,
+        "right_paddle_height" => changed.right_paddle_height = parse(value)?,
+        "ball_growth" => changed.ball_growth = parse(value)?,
+        "move_during_delay" => changed.move_during_delay = parse(value)?,
+        "restitution" => changed.restitution = parse(value)?,
+        "ai_miss_chance" => changed.ai_miss_chance = parse(value)?,
+        "starting_lives" => changed.starting_lives = parse(value)?,
+        "max_balls" => changed.max_balls = parse(value)?,
+        "elimination" => changed.elimination = parse(value)?,
+        "zen" => changed.zen = parse(value)?,
+        "multi_ball" => changed.multi_ball = parse(value)?,
+        "doubles" => changed.doubles = parse(value)?,
+        // `none` plays without a score limit
+        "winning_score" => {
+            changed.winning_score = match value {
+                "none" => None,
+                _ => Some(parse(value)?),
+            }
+        }
+        // In seconds
+        "serve_delay" => {
+            changed.serve_delay = Duration::try_from_secs_f32(parse(value)?)
+                .map_err(|_| format!("{value} is not a valid delay"))?
+        }
+        _ => return Err(format!("unknown setting {setting}")),
+    }
+    changed.validate().map_err(|e| e.to_string())?;
+
+    *settings = changed;
+    Ok(format!("{setting} = {value}"))
+}
+
+/// The developer console's state: whether it's open, what's being typed and recent output.
+#[derive(Debug, Clone, Default)]
+pub struct Console {
+    pub open: bool,
+    pub input: String,
+    pub history: Vec<String>,
+}
+
+impl Console {
+    /// Runs the typed line against `settings` and records it and its result.
+    pub fn submit(&mut self, settings: &mut GameSettings) {
+        let line = std::mem::take(&mut self.input);
+        let output = match parse_command(&line) {
+            Ok(Command::Set { setting, value }) => apply_set(settings, &setting, &value),
+            Ok(Command::Help) => Ok(format!("set <setting> <value>: {}", SETTABLE.join(", "))),
+            Err(e) => Err(e),
+        };
+        self.push(format!("> {line}"));
+        self.push(output.unwrap_or_else(|e| format!("error: {e}")));
+    }
+
+    fn push(&mut self, line: String) {
+        self.history.push(line);
+        if self.history.len() > CONSOLE_HISTORY {
+            self.history.remove(0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commands_are_parsed() {
+        assert_eq!(
+            parse_command("  set ball_speed  7 "),
+            Ok(Command::Set {
+                setting: "ball_speed".to_string(),
+                value: "7".to_string(),
+            })
+        );
+        assert_eq!(parse_command("help"), Ok(Command::Help));
+        assert!(parse_command("set ball_speed").is_err());
+        assert!(parse_command("").is_err());
+        assert!(parse_command("jump 3").is_err());
+    }
+
+    #[test]
+    fn set_changes_only_valid_settings() {
+        let mut settings = GameSettings::default();
+        assert_eq!(
+            apply_set(&mut settings, "zen", "true"),
+            Ok("zen = true".to_string())
+        );
+        assert!(settings.zen);
+
+        let before = settings.clone();
+        assert!(apply_set(&mut settings, "ball_speed", "fast").is_err());
+        assert!(apply_set(&mut settings, "ball_speed", "1000").is_err());
+        assert!(apply_set(&mut settings, "gravity", "1").is_err());
+        assert!(apply_set(&mut settings, "serve_delay", "-1").is_err());
+        assert_eq!(settings, before);
+    }
+
+    #[test]
+    fn set_changes_the_win_condition_and_serve_delay() {
+        let mut settings = GameSettings::default();
+        apply_set(&mut settings, "winning_score", "11").unwrap();
+        assert_eq!(settings.winning_score, Some(11));
+        apply_set(&mut settings, "winning_score", "none").unwrap();
+        assert_eq!(settings.winning_score, None);
+
+        apply_set(&mut settings, "serve_delay", "0.5").unwrap();
+        assert_eq!(settings.serve_delay, Duration::from_millis(500));
+        apply_set(&mut settings, "serve_delay", "0").unwrap();
+        assert_eq!(settings.serve_delay, Duration::ZERO);
+    }
+
+    #[test]
+    fn submitted_lines_are_echoed_with_their_result() {
+        let mut console = Console {
+            input: "set max_balls 4".to_string(),
+            ..Console::default()
+        };
+        let mut settings = GameSettings::default();
+        console.submit(&mut settings);
+
+        assert_eq!(settings.max_balls, 4);
+        assert!(console.input.is_empty());
+        assert_eq!(console.history, vec!["> set max_balls 4", "max_balls = 4"]);
+    }
+}
@@ -0,0 +1,30 @@
+use ggez::{
+    graphics::{Color, Rect},
+    mint::Point2,
+};
+
+/// A game object on the field as it should be drawn, independent of how it's drawn.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Drawable {
+    Paddle {
+        rect: Rect,
+        color: Color,
+    },
+    Obstacle {
+        rect: Rect,
+        color: Color,
+    },
+    PowerUp {
+        rect: Rect,
+        color: Color,
+    },
+    /// A ball turned by `rotation` radians to face where it's going and stretched along that
+    /// direction by `stretch`
+    Ball {
+        pos: Point2<f32>,
+        radius: f32,
+        rotation: f32,
+        stretch: f32,
+        color: Color,
+    },
+}
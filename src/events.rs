@@ -0,0 +1,25 @@
+use std::time::Duration;
+
+use crate::side::Side;
+
+/// Something notable that happened during a simulation step.
+/// Events are queued by the physics and drained by `update`, which has the `Context`
+/// needed to react to them (e.g. playing sounds).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GameEvent {
+    PaddleHit(Side),
+    WallBounce,
+    Scored(Side),
+    /// The deciding point was scored
+    MatchWon {
+        winner: Side,
+    },
+}
+
+/// A `GameEvent` stamped with the match time it happened at.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimedEvent {
+    pub event: GameEvent,
+    /// Match time elapsed when the event fired, excluding time spent on other screens
+    pub time: Duration,
+}
@@ -0,0 +1,42 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use crate::input::InputFrame;
+use crate::snapshot::Snapshot;
+
+/// Environment variable naming the file to write the per-frame debug log to
+pub const FRAME_LOG_ENV_VAR: &str = "PONG_FRAME_LOG";
+
+/// Opt-in debug log of every frame's input and resulting state hash,
+/// for comparing two runs (e.g. host and client) to find where they diverge.
+pub struct FrameLogger {
+    writer: BufWriter<File>,
+    frame: u64,
+}
+
+impl FrameLogger {
+    pub fn create(path: impl AsRef<Path>) -> std::io::Result<FrameLogger> {
+        Ok(FrameLogger {
+            writer: BufWriter::new(File::create(path)?),
+            frame: 0,
+        })
+    }
+
+    pub fn log(&mut self, input: &InputFrame, snapshot: &Snapshot) -> std::io::Result<()> {
+        writeln!(
+            self.writer,
+            "{} {:016x} pressed={:?} just_pressed={:?} controller={}:{}",
+            self.frame,
+            snapshot.stable_hash(),
+            input.pressed,
+            input.just_pressed,
+            input.controller_connected,
+            input.controller_axis
+        )?;
+        self.frame += 1;
+        Ok(())
+    }
+}
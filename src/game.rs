@@ -1,285 +1,5176 @@
+use std::time::{Instant, SystemTime};
+
 use ggez::{
     Context, GameResult,
+    conf::WindowMode,
     event::EventHandler,
     graphics::{self, Color, Rect},
-    input::keyboard::{KeyCode, KeyboardContext},
+    input::keyboard::KeyCode,
     mint::Point2,
 };
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+use crate::ai::{self, Ai};
+#[cfg(feature = "audio")]
+use crate::audio::Audio;
+use crate::background::Background;
+use crate::ball::{BALL_RADIUS, Ball};
+use crate::config::{CONFIG_PATH, Config, GameplayConfig};
+use crate::console::{self, Console};
+use crate::drawable::Drawable;
+use crate::events::{GameEvent, TimedEvent};
+use crate::frame_log::FrameLogger;
+use crate::geometry::{self, intersects};
+use crate::heat::HeatMeter;
+use crate::hud;
+use crate::input::InputFrame;
+use crate::obstacles::{self, BURST_SPEED_FACTOR, MAX_OBSTACLES};
+use crate::paddle::{DASH_COOLDOWN_FRAMES, PADDLE_WIDTH, Paddle, Wall};
+use crate::powerups::{self, MAX_POWER_UPS, PowerUp, PowerUpKind, PowerUpSpawner};
+use crate::records::Records;
+use crate::rewind::{MatchRecording, Rewind};
+use crate::screenshot;
+use crate::settings::{
+    BoardPreset, GameMode, GameSettings, HitSpeed, Opponent, PaddleLink, ServeRule, cycled,
+};
+use crate::side::Side;
+use crate::snapshot::Snapshot;
+use crate::stats::MatchStats;
+use crate::theme::{self, BackgroundStyle, Theme, ThemeElement};
+use crate::training::TrainingTargets;
+use crate::walls::{self, Edge, WallBehavior};
 
 /// Constants
-const PADDLE_SPEED: f32 = 5.0;
-const BALL_SPEED: f32 = 6.0;
-const PADDLE_WIDTH: f32 = 20.0;
-const PADDLE_HEIGHT: f32 = 100.0;
-const BALL_RADIUS: f32 = 8.0;
+const GOAL_FLASH_DURATION: std::time::Duration = std::time::Duration::from_millis(400);
+const GOAL_FLASH_WIDTH: f32 = 10.0;
+const GO_FLASH_DURATION: std::time::Duration = std::time::Duration::from_millis(300);
+// How often a trailing player gets a mercy ball once they're far enough behind
+const MERCY_BALL_CHANCE: f64 = 0.5;
+// Frames scrubbed per update on the results screen while Shift is held
+const SCRUB_FAST_FRAMES: isize = 10;
+// Height of the results screen's scrub bar and its gap from the bottom of the window
+const SCRUB_BAR_HEIGHT: f32 = 6.0;
+const SCRUB_BAR_MARGIN: f32 = 20.0;
+// Redoing a round with R is only allowed this often
+const ROUND_RESET_COOLDOWN: std::time::Duration = std::time::Duration::from_secs(3);
+// The coin flip spins for its duration, showing its result for the last part of it
+const COIN_FLIP_DURATION: std::time::Duration = std::time::Duration::from_millis(1300);
+const COIN_FLIP_SETTLE: std::time::Duration = std::time::Duration::from_millis(400);
+const COIN_FLIP_SPIN_INTERVAL_MS: u128 = 80;
+// With serve grace on, a serve starts at this fraction of its speed and ramps up over the duration
+const SERVE_GRACE_DURATION: std::time::Duration = std::time::Duration::from_millis(500);
+const SERVE_GRACE_START_SPEED: f32 = 0.5;
+// With match-point slow-mo on, the ball moves at this share of its speed during a deciding rally
+const MATCH_POINT_TIME_SCALE: f32 = 0.8;
+// The beginner assist's first serve is this much slower and flatter, easing up to full
+// difficulty over this many points
+const ASSIST_START_SPEED: f32 = 0.7;
+const ASSIST_START_ANGLE: f32 = 0.3;
+const ASSIST_RAMP_POINTS: u32 = 10;
+// In multi-ball mode, another ball joins every time a rally reaches a multiple of this many hits
+const MULTI_BALL_SPAWN_HITS: u32 = 4;
+// A ball this far outside the field can only have got there through a physics glitch
+const ESCAPE_MARGIN: f32 = 100.0;
+// Dash pattern of the predicted path shown in practice mode
+const PREDICTION_DASH: f32 = 10.0;
+const PREDICTION_GAP: f32 = 8.0;
+const STATS_EXPORT_PATH: &str = "match_stats.json";
+const RECORDS_PATH: &str = "records.json";
+const BEST_RALLY_EXPORT_PATH: &str = "best_rally.json";
+// The stuck-ball watchdog steps in after this many frames of near-vertical drifting
+const STUCK_BALL_MIN_X_SPEED: f32 = 0.5;
+const STUCK_BALL_FRAME_LIMIT: u32 = 90;
+const HEAT_BAR_WIDTH: f32 = 100.0;
+const HEAT_BAR_HEIGHT: f32 = 6.0;
+const HEAT_BAR_Y: f32 = 75.0;
+/// Length and strength of the celebration of a match's deciding point
+const CELEBRATION_DURATION: std::time::Duration = std::time::Duration::from_millis(2500);
+const CELEBRATION_FLASHES: f32 = 4.0;
+const CELEBRATION_ZOOM: f32 = 0.08;
+const CELEBRATION_SHAKE: f32 = 12.0;
+const CELEBRATION_SHAKE_RATE: f32 = 90.0;
+const SPEED_LINE_WIDTH: f32 = 2.0;
+const SPEED_LINE_PIECES: usize = 3;
+const WALL_TINT_HEIGHT: f32 = 4.0;
+const DASH_BAR_GAP: f32 = 4.0;
+const DASH_BAR_HEIGHT: f32 = 3.0;
+/// Name the font from `DisplaySettings::font` is registered under
+const CUSTOM_FONT: &str = "custom";
+const LIFE_ICON_RADIUS: f32 = 5.0;
+/// Rows of the settings screen shown at once, scrolling to keep the selection in view
+const SETTINGS_ROWS_SHOWN: usize = 13;
+const LIFE_ICON_SPACING: f32 = 16.0;
+// The debug grid's line spacing, label size and the size of its position markers
+const DEBUG_GRID_SPACING: f32 = 50.0;
+const DEBUG_GRID_LABEL_SCALE: f32 = 12.0;
+const DEBUG_MARKER_SIZE: f32 = 6.0;
+// How much a held R/G/B key changes a color channel each frame on the color picker
+const COLOR_PICKER_STEP: i32 = 2;
+
+/// Which screen is currently shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Screen {
+    Menu,
+    Playing,
+    Settings,
+    /// Builds a custom theme, opened from the settings screen
+    ColorPicker,
+    /// Shown once a match is over
+    Summary,
+}
+
+/// Choices offered on the match summary screen, in display order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SummaryOption {
+    Rematch,
+    ExportBestRally,
+    ChangeSettings,
+    MainMenu,
+}
+
+impl SummaryOption {
+    const ALL: [SummaryOption; 4] = [
+        SummaryOption::Rematch,
+        SummaryOption::ExportBestRally,
+        SummaryOption::ChangeSettings,
+        SummaryOption::MainMenu,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            SummaryOption::Rematch => "Rematch",
+            SummaryOption::ExportBestRally => "Export best rally",
+            SummaryOption::ChangeSettings => "Change settings",
+            SummaryOption::MainMenu => "Main menu",
+        }
+    }
+}
+
+/// Rows of the settings screen, in display order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SettingsItem {
+    BallSpeed,
+    ServeAngle,
+    SoundEffects,
+    Music,
+    Volume,
+    SpeedPitch,
+    Background,
+    HitSpeed,
+    ServeGrace,
+    MatchPointSlowMo,
+    BeginnerAssist,
+    DeadBallTimeout,
+    MultiBall,
+    Doubles,
+    TrainingTargets,
+    Practice,
+    ServeOnKeypress,
+    ServeDelay,
+    Comeback,
+    MercyBall,
+    CoinFlip,
+    AiMissChance,
+    PaddleLink,
+    Sensitivity,
+    KeyboardSensitivity,
+    HeatMeter,
+    ServeRule,
+    MirrorView,
+    Restitution,
+    SpectatorHud,
+    SplitScore,
+    ScaleToWindow,
+    FairServe,
+    ReadyCheck,
+    Warmup,
+    Obstacles,
+    FixedObstacles,
+    Wall(Edge),
+    Net,
+    SpeedBurst,
+    PowerUps,
+    PaddleMargin,
+    PaddleHeight(Side),
+    MoveDuringDelay,
+    BallGrowth,
+    WinningScore,
+    WinByTwo,
+}
+
+impl SettingsItem {
+    const ALL: [SettingsItem; 51] = [
+        SettingsItem::BallSpeed,
+        SettingsItem::ServeAngle,
+        SettingsItem::SoundEffects,
+        SettingsItem::Music,
+        SettingsItem::Volume,
+        SettingsItem::SpeedPitch,
+        SettingsItem::Background,
+        SettingsItem::HitSpeed,
+        SettingsItem::ServeGrace,
+        SettingsItem::MatchPointSlowMo,
+        SettingsItem::BeginnerAssist,
+        SettingsItem::DeadBallTimeout,
+        SettingsItem::MultiBall,
+        SettingsItem::Doubles,
+        SettingsItem::TrainingTargets,
+        SettingsItem::Practice,
+        SettingsItem::ServeOnKeypress,
+        SettingsItem::ServeDelay,
+        SettingsItem::Comeback,
+        SettingsItem::MercyBall,
+        SettingsItem::CoinFlip,
+        SettingsItem::AiMissChance,
+        SettingsItem::PaddleLink,
+        SettingsItem::Sensitivity,
+        SettingsItem::KeyboardSensitivity,
+        SettingsItem::HeatMeter,
+        SettingsItem::ServeRule,
+        SettingsItem::MirrorView,
+        SettingsItem::Restitution,
+        SettingsItem::SpectatorHud,
+        SettingsItem::SplitScore,
+        SettingsItem::ScaleToWindow,
+        SettingsItem::FairServe,
+        SettingsItem::ReadyCheck,
+        SettingsItem::Warmup,
+        SettingsItem::Obstacles,
+        SettingsItem::FixedObstacles,
+        SettingsItem::Wall(Edge::Top),
+        SettingsItem::Wall(Edge::Bottom),
+        SettingsItem::Wall(Edge::Left),
+        SettingsItem::Wall(Edge::Right),
+        SettingsItem::Net,
+        SettingsItem::SpeedBurst,
+        SettingsItem::PowerUps,
+        SettingsItem::PaddleMargin,
+        SettingsItem::PaddleHeight(Side::Left),
+        SettingsItem::PaddleHeight(Side::Right),
+        SettingsItem::BallGrowth,
+        SettingsItem::MoveDuringDelay,
+        SettingsItem::WinningScore,
+        SettingsItem::WinByTwo,
+    ];
+}
 
 pub struct GameState {
-    left_paddle_pos: Point2<f32>,
-    right_paddle_pos: Point2<f32>,
-    ball_pos: Point2<f32>,
-    ball_vel: Point2<f32>,
+    screen: Screen,
+    // Where closing the settings screen returns to
+    screen_before_settings: Screen,
+    // Time left in the celebration of the match's deciding point
+    celebration: Option<std::time::Duration>,
+    // Obstacles in the middle of the field that the ball bounces off
+    obstacles: Vec<Rect>,
+    // Which players have signalled they're ready, while a ready check holds the first serve
+    ready: Option<(bool, bool)>,
+    // The match hasn't started counting yet and points aren't scored
+    warmup: bool,
+    // Which players have pressed their serve key to end the warmup
+    warmup_ready: (bool, bool),
+    // Whether serve key presses count towards ending the warmup. Cleared by `start_match` so
+    // the Return that started the match doesn't also ready the right player.
+    warmup_armed: bool,
+    // Index into `GameMode::ALL` of the highlighted main menu mode
+    menu_selection: usize,
+    // Index into `SummaryOption::ALL` of the highlighted summary option
+    summary_selection: usize,
+    // Index into `SettingsItem::ALL` of the highlighted settings row
+    settings_selection: usize,
+    // Index into `ThemeElement::ALL` of the element being colored on the color picker
+    color_picker_selection: usize,
+    config: Config,
+    // Name the custom font was registered under, if it loaded
+    font: Option<&'static str>,
+    // Not available when running headless
+    #[cfg(feature = "audio")]
+    audio: Option<Audio>,
+    // Events raised by the physics since the last update
+    events: Vec<TimedEvent>,
+    settings: GameSettings,
+    left_paddle: Paddle,
+    right_paddle: Paddle,
+    // The trailing player currently given a bigger paddle
+    comeback: Option<Side>,
+    // Drives the right paddle when the opponent is the AI
+    ai: Ai,
+    ball: Ball,
+    // Balls added on top of the main one in multi-ball mode
+    extra_balls: Vec<Ball>,
     score: (u8, u8), // Play until this overflows ^.^
+    // Remaining lives in elimination mode
+    lives: (u8, u8),
+    // Set once the match is over
+    winner: Option<Side>,
     // Represents a user-initiated pause
     paused: bool,
+    // Set while the window is minimized, holding whether play was already paused beforehand
+    minimized: Option<bool>,
+    // Whether a controller was plugged in last frame
+    controller_connected: bool,
+    // The controller was unplugged mid-match and play is paused until it returns
+    controller_lost: bool,
     // Delay duration is used to prevent the ball from moving for a short time after a score
     delay_duration: std::time::Duration,
+    // The wall that was just scored on, and how long its flash has left
+    goal_flash: Option<(Side, std::time::Duration)>,
+    // Time left on the "GO!" shown when play resumes after the delay
+    go_flash: std::time::Duration,
+    // Time since a paddle last touched the ball, for the dead-ball timeout
+    since_paddle_hit: std::time::Duration,
+    // Time until R can redo the round again
+    round_reset_cooldown: std::time::Duration,
+    // How fast the balls move relative to normal, slowed on match point
+    time_scale: f32,
+    // Time left in the current serve's slow-start grace period
+    serve_grace: std::time::Duration,
+    // The player who served the current ball
+    server: Side,
+    // Who serves first in a match, picked on the menu. `None` leaves it to chance.
+    first_server: Option<Side>,
+    // The coin flip deciding the first server and how long its animation has left
+    coin_flip: Option<(Side, std::time::Duration)>,
+    // Paddle hits since the current ball was served
+    rally_hits: u32,
+    // Time spent playing this match, excluding pauses
+    match_time: std::time::Duration,
+    stats: MatchStats,
+    // Matches won per mode, saved to `RECORDS_PATH` after each one unless running headless
+    records: Records,
+    save_records: bool,
+    training: TrainingTargets,
+    // How much paddle and ball sizes are shrunk to fit a small window
+    size_scale: f32,
+    // The wall each player is holding their paddle against this frame
+    pressed_walls: (Option<Wall>, Option<Wall>),
+    // Fills up during the current rally when the heat meter is on
+    heat: HeatMeter,
+    // The net along the center line, if it's up
+    net: Option<Rect>,
+    // The band that speeds up balls entering it, if it's on
+    burst_zone: Option<Rect>,
+    // Power-ups waiting to be collected, and what puts them on the field
+    power_ups: Vec<PowerUp>,
+    power_up_spawner: PowerUpSpawner,
+    // Players whose up and down keys are swapped by an invert power-up until the round ends
+    inverted: (bool, bool),
+    // Backtick opens it to change settings by typing commands
+    console: Console,
+    theme: Theme,
+    background: Background,
+    // Consecutive frames the ball has spent with almost no horizontal speed
+    stuck_frames: u32,
+    frame_logger: Option<FrameLogger>,
+    // Where the ball is headed, shown in practice mode
+    predicted_path: Vec<Point2<f32>>,
+    // F12/PrintScreen was pressed and the next drawn frame should be saved
+    screenshot_requested: bool,
+    screenshots_taken: u32,
+    // Whether the debug coordinate grid is showing, `None` unless debugging is enabled
+    debug_grid: Option<bool>,
+    // Debug frame history, only kept when enabled
+    rewind: Option<Rewind>,
+    // Every frame of the current match, scrubbed through on the results screen
+    recording: MatchRecording,
+    // When the last frame started, for holding to the configured frame cap
+    last_frame_start: Option<Instant>,
+    // Source of all gameplay randomness, seedable for reproducible matches
+    rng: StdRng,
 }
 
 impl GameState {
-    /// Generates a random ball velocity with a random angle
-    /// Avoids angles too close to π/2 to prevent excessive vertical bouncing
-    fn random_ball_velocity() -> Point2<f32> {
-        // Generate angle between -π/3 and π/3 (avoiding the vertical range)
-        // This ensures the ball has a reasonable horizontal component
-        let angle = (rand::random::<f32>() - 0.5) * 2.0 * std::f32::consts::PI / 3.0;
-
-        let x_direction = if rand::random::<f32>() < 0.5 {
-            1.0
-        } else {
-            -1.0
-        };
+    pub fn new(ctx: &mut Context, config: Config) -> GameResult<GameState> {
+        let (width, height) = ctx.gfx.drawable_size();
 
-        Point2 {
-            x: x_direction * angle.cos() * BALL_SPEED,
-            y: angle.sin() * BALL_SPEED,
+        let mut state = Self::with_bounds(width, height, GameSettings::default());
+        #[cfg(feature = "audio")]
+        {
+            state.audio = Some(Audio::new(ctx, &config.audio));
         }
-    }
+        if let Some(path) = config.display.font_path() {
+            match graphics::FontData::from_path(&ctx.fs, &path) {
+                Ok(font) => {
+                    ctx.gfx.add_font(CUSTOM_FONT, font);
+                    state.font = Some(CUSTOM_FONT);
+                }
+                Err(e) => log::warn!("Could not load font {path}, using the default: {e}"),
+            }
+        }
+        if let Some(custom_theme) = config.custom_theme {
+            custom_theme.apply(&mut state.theme);
+        }
+        config.gameplay.apply(&mut state.settings);
+        state.fit_to_bounds(width, height);
+        state.config = config;
+        state.records = Records::load(RECORDS_PATH);
+        state.save_records = true;
 
-    pub fn new(ctx: &mut Context) -> GameResult<GameState> {
-        ctx.gfx.set_window_title("Rusty Pong");
-        let (width, height) = ctx.gfx.drawable_size();
+        Ok(state)
+    }
 
-        Ok(GameState {
-            left_paddle_pos: Point2 {
-                x: 20.,
-                y: (height / 2.) - (PADDLE_HEIGHT / 2.),
-            },
-            right_paddle_pos: Point2 {
-                x: width - PADDLE_WIDTH - 20.,
-                y: (height / 2.) - (PADDLE_HEIGHT / 2.),
-            },
-            ball_pos: Point2 {
-                x: (width / 2.),
-                y: (height / 2.),
+    /// Builds a fully initialized state for a field of the given size, waiting on the menu.
+    /// Unlike `new`, this doesn't need a `Context`, so the simulation can be driven headlessly
+    /// once `start_match` is called.
+    pub fn with_bounds(width: f32, height: f32, settings: GameSettings) -> GameState {
+        let mut rng = match settings.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_os_rng(),
+        };
+        let mut state = GameState {
+            screen: Screen::Menu,
+            screen_before_settings: Screen::Menu,
+            obstacles: Vec::new(),
+            celebration: None,
+            ready: None,
+            warmup: false,
+            warmup_ready: (false, false),
+            warmup_armed: false,
+            menu_selection: 0,
+            summary_selection: 0,
+            settings_selection: 0,
+            color_picker_selection: 0,
+            config: Config::default(),
+            font: None,
+            #[cfg(feature = "audio")]
+            audio: None,
+            events: Vec::new(),
+            lives: (settings.starting_lives, settings.starting_lives),
+            winner: None,
+            left_paddle: Paddle::new(0., PADDLE_WIDTH, settings.left_paddle_height, height),
+            right_paddle: Paddle::new(0., PADDLE_WIDTH, settings.right_paddle_height, height),
+            size_scale: 1.,
+            comeback: None,
+            ai: Ai {
+                target_y: height / 2.,
+                miss_offset: 0.,
+                incoming: false,
+                speed: settings.paddle_speed,
             },
-            ball_vel: Self::random_ball_velocity(),
+            ball: Ball::new(
+                Point2 { x: 0., y: 0. },
+                settings.ball_speed,
+                settings.max_serve_angle,
+                &mut rng,
+            ),
+            extra_balls: Vec::new(),
             score: (0, 0),
             paused: false,
+            minimized: None,
+            controller_connected: false,
+            controller_lost: false,
             delay_duration: std::time::Duration::ZERO,
-        })
+            goal_flash: None,
+            go_flash: std::time::Duration::ZERO,
+            round_reset_cooldown: std::time::Duration::ZERO,
+            since_paddle_hit: std::time::Duration::ZERO,
+            time_scale: 1.,
+            serve_grace: std::time::Duration::ZERO,
+            server: Side::Left,
+            first_server: None,
+            coin_flip: None,
+            rally_hits: 0,
+            match_time: std::time::Duration::ZERO,
+            stats: MatchStats::default(),
+            records: Records::default(),
+            save_records: false,
+            training: TrainingTargets::default(),
+            heat: HeatMeter::default(),
+            net: None,
+            burst_zone: None,
+            power_ups: Vec::new(),
+            power_up_spawner: PowerUpSpawner::new(std::time::Duration::ZERO),
+            inverted: (false, false),
+            console: Console::default(),
+            pressed_walls: (None, None),
+            theme: Theme::default(),
+            background: Background::default(),
+            stuck_frames: 0,
+            frame_logger: None,
+            debug_grid: None,
+            rewind: None,
+            recording: MatchRecording::default(),
+            screenshot_requested: false,
+            screenshots_taken: 0,
+            predicted_path: Vec::new(),
+            last_frame_start: None,
+            rng,
+            settings,
+        };
+        state.reset_round(width, height);
+
+        state
     }
 
-    /// Takes the keyboard context and handles the paddle movement.
-    fn handle_paddle_movement(&mut self, keyboard: &KeyboardContext) -> GameResult {
-        let mut pos_change = 0.;
+    pub fn set_frame_logger(&mut self, logger: FrameLogger) {
+        self.frame_logger = Some(logger);
+    }
 
-        for key in keyboard.pressed_keys() {
-            match key {
-                // Note: origin is TLC
-                KeyCode::W => pos_change = -1.,
-                KeyCode::S => pos_change = 1.,
-                _ => {} // Ignore other keys
-            }
-        }
+    pub fn score(&self) -> (u8, u8) {
+        self.score
+    }
 
-        self.left_paddle_pos.y += pos_change * PADDLE_SPEED;
-        self.right_paddle_pos.y += pos_change * PADDLE_SPEED;
+    /// Who has won the match under its active win conditions, if anyone. Once decided, the
+    /// result stands until the next match starts.
+    pub fn winner(&self) -> Option<Side> {
+        self.winner.or_else(|| self.decided_winner())
+    }
 
-        Ok(())
+    /// The winner the current state calls for: the last player with lives left in elimination,
+    /// the first to the winning score, or the leader once a time attack's clock has run out.
+    /// A level score plays on.
+    fn decided_winner(&self) -> Option<Side> {
+        self.winner_for(self.score, self.lives)
     }
 
-    /// Handles the ball movement.
-    /// Ball moves and may collide with the paddles.
-    fn handle_ball_movement(&mut self, y_bound: f32) -> GameResult {
-        // Do basic movement
-        self.ball_pos.x += self.ball_vel.x;
-        self.ball_pos.y += self.ball_vel.y;
+    /// The winner a given score and lives would call for at the current match time.
+    fn winner_for(&self, score: (u8, u8), lives: (u8, u8)) -> Option<Side> {
+        if self.settings.elimination {
+            if lives.0 == 0 {
+                return Some(Side::Right);
+            }
+            if lives.1 == 0 {
+                return Some(Side::Left);
+            }
+        }
+        if let Some(target) = self.settings.winning_score {
+            let lead = if self.settings.win_by_two { 2 } else { 1 };
+            if let Some(side) = [Side::Left, Side::Right].into_iter().find(|side| {
+                let (own, other) = (*side.of(&score), *side.opposite().of(&score));
+                own >= target && own >= other.saturating_add(lead)
+            }) {
+                return Some(side);
+            }
+        }
+        if let Some(limit) = self.settings.time_limit
+            && self.match_time >= limit
+        {
+            return match score.0.cmp(&score.1) {
+                std::cmp::Ordering::Greater => Some(Side::Left),
+                std::cmp::Ordering::Less => Some(Side::Right),
+                std::cmp::Ordering::Equal => None,
+            };
+        }
+        None
+    }
 
-        let speed = (self.ball_vel.x * self.ball_vel.x + self.ball_vel.y * self.ball_vel.y).sqrt();
+    /// Ends the match as soon as one of its win conditions is met.
+    fn check_for_winner(&mut self) {
+        if self.winner.is_none()
+            && let Some(winner) = self.decided_winner()
+        {
+            self.win_match(winner);
+        }
+    }
 
-        let left_center_y = self.left_paddle_pos.y + (PADDLE_HEIGHT / 2.);
-        let left_surface_x = self.left_paddle_pos.x + PADDLE_WIDTH;
+    /// Hands over the events raised since the last call.
+    pub fn take_events(&mut self) -> Vec<TimedEvent> {
+        std::mem::take(&mut self.events)
+    }
 
-        // Handle collisions with the left paddle
-        if self.ball_pos.x - BALL_RADIUS < left_surface_x
-            && self.ball_pos.x - BALL_RADIUS > left_surface_x - PADDLE_WIDTH
+    /// Sleeps off whatever is left of the frame when a frame cap is set.
+    fn wait_for_frame_cap(&mut self) {
+        if let Some(min_frame_time) = self.config.display.min_frame_time()
+            && let Some(last_frame_start) = self.last_frame_start
         {
-            let distance_from_center = self.ball_pos.y - left_center_y;
-            let angle = distance_from_center / (PADDLE_HEIGHT / 2.);
-            if angle.abs() <= 1. {
-                self.ball_vel.x = angle.cos() * speed;
-                self.ball_vel.y = angle.sin() * speed;
+            let remaining = min_frame_time.saturating_sub(last_frame_start.elapsed());
+            if !remaining.is_zero() {
+                std::thread::sleep(remaining);
             }
         }
+        self.last_frame_start = Some(Instant::now());
+    }
 
-        let right_center_y = self.right_paddle_pos.y + (PADDLE_HEIGHT / 2.);
-        let right_surface_x = self.right_paddle_pos.x;
+    /// Lets G toggle the debug coordinate grid.
+    pub fn enable_debug_grid(&mut self) {
+        self.debug_grid = Some(false);
+    }
 
-        // Handle collisions with the right paddle
-        if self.ball_pos.x + BALL_RADIUS > right_surface_x
-            && self.ball_pos.x + BALL_RADIUS < right_surface_x + PADDLE_WIDTH
-        {
-            let distance_from_center = self.ball_pos.y - right_center_y;
+    /// Turns on the debug frame history.
+    pub fn enable_rewind(&mut self) {
+        self.rewind = Some(Rewind::default());
+    }
+
+    /// With the frame history on, Comma/Period step back and forward through it.
+    fn handle_rewind_input(&mut self, input: &InputFrame) {
+        let Some(rewind) = &mut self.rewind else {
+            return;
+        };
+
+        let snapshot = if input.just_pressed.contains(&KeyCode::Comma) {
+            rewind.step_back().cloned()
+        } else if input.just_pressed.contains(&KeyCode::Period) {
+            rewind.step_forward().cloned()
+        } else {
+            None
+        };
 
-            let angle = distance_from_center / (PADDLE_HEIGHT / 2.);
-            if angle.abs() <= 1. {
-                // Flip x velocity since it's coming from the right
-                self.ball_vel.x = angle.cos() * -speed;
-                self.ball_vel.y = angle.sin() * speed;
+        if let Some(snapshot) = snapshot {
+            self.restore(&snapshot);
+        }
+    }
+
+    /// Puts the ball, paddles and score back where `snapshot` captured them. Nothing else is
+    /// touched, so this is only for showing a past frame, never for playing on from one.
+    pub fn restore(&mut self, snapshot: &Snapshot) {
+        self.ball.pos = snapshot.ball_pos;
+        self.ball.vel = snapshot.ball_vel;
+        self.left_paddle.pos.y = snapshot.left_paddle_y;
+        self.right_paddle.pos.y = snapshot.right_paddle_y;
+        self.score = snapshot.score;
+    }
+
+    /// Saves the last drawn frame, logging rather than failing if it can't be written.
+    fn take_screenshot(&mut self, ctx: &mut Context) {
+        let since_epoch = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default();
+        let path = screenshot::file_name(since_epoch, self.screenshots_taken);
+        self.screenshots_taken += 1;
+
+        match screenshot::save(ctx, &path) {
+            Ok(()) => log::info!("Saved screenshot {path}"),
+            Err(e) => log::warn!("Could not save screenshot {path}: {e}"),
+        }
+    }
+
+    /// Captures the current dynamic state of the match.
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            ball_pos: self.ball.pos,
+            ball_vel: self.ball.vel,
+            left_paddle_y: self.left_paddle.pos.y,
+            right_paddle_y: self.right_paddle.pos.y,
+            score: self.score,
+        }
+    }
+
+    /// Returns true if the given bounds describe a usable play field.
+    /// A minimized window can report a drawable size of zero.
+    fn has_valid_bounds(width: f32, height: f32) -> bool {
+        width > 0. && height > 0.
+    }
+
+    /// Pauses play while the window is minimized, which shows up as a degenerate drawable size,
+    /// and resumes it once the window is restored. A game paused beforehand stays paused.
+    pub fn handle_minimize(&mut self, width: f32, height: f32) {
+        let valid = Self::has_valid_bounds(width, height);
+        match self.minimized {
+            None if !valid => {
+                log::info!("Window minimized, pausing");
+                self.minimized = Some(self.paused);
+                self.paused = true;
             }
+            Some(was_paused) if valid => {
+                log::info!("Window restored");
+                self.minimized = None;
+                self.paused = was_paused;
+            }
+            _ => {}
+        }
+    }
+
+    /// Moves everything tied to the field's edges onto a field of the new size.
+    fn fit_to_bounds(&mut self, width: f32, height: f32) {
+        if !Self::has_valid_bounds(width, height) {
+            return;
         }
 
-        // Handle collisions with horizontal walls
-        if self.ball_pos.y - BALL_RADIUS < 0. || self.ball_pos.y + BALL_RADIUS > y_bound {
-            self.ball_vel.y = -self.ball_vel.y;
+        self.update_size_scale(width, height);
+        self.left_paddle.width = self.paddle_width();
+        self.left_paddle.height = self.paddle_height(Side::Left);
+        self.right_paddle.width = self.paddle_width();
+        self.right_paddle.height = self.paddle_height(Side::Right);
+
+        // Keep the paddles and targets glued to their wall
+        self.left_paddle.pos.x = self.settings.paddle_margin;
+        self.right_paddle.pos.x = self.right_paddle_x(width);
+        self.training.layout(width, height);
+        self.left_paddle.clamp_to(height);
+        self.right_paddle.clamp_to(height);
+    }
+
+    /// Re-centers the ball and paddles and serves a fresh ball.
+    /// Does nothing if the bounds are degenerate, so objects keep their last sane positions.
+    fn reset_round(&mut self, width: f32, height: f32) {
+        if !Self::has_valid_bounds(width, height) {
+            return;
         }
 
-        Ok(())
+        self.update_size_scale(width, height);
+        self.serve_from_center(width, height);
+        self.power_ups.clear();
+        self.inverted = (false, false);
+        self.net = obstacles::net_rect(self.settings.net_height, width, height);
+        self.burst_zone = self
+            .settings
+            .speed_burst
+            .then(|| obstacles::burst_zone_rect(width, height));
+        self.training.layout(width, height);
+        if !self.settings.fixed_obstacles || self.obstacles.is_empty() {
+            self.obstacles = obstacles::place_obstacles(
+                self.settings.obstacle_count,
+                width,
+                height,
+                &mut self.rng,
+            );
+        }
+
+        self.left_paddle = Paddle::new(
+            self.settings.paddle_margin,
+            self.paddle_width(),
+            self.paddle_height(Side::Left),
+            height,
+        );
+        self.right_paddle = Paddle::new(
+            self.right_paddle_x(width),
+            self.paddle_width(),
+            self.paddle_height(Side::Right),
+            height,
+        );
+        self.ai = Ai::new(&self.right_paddle, self.settings.paddle_speed);
+    }
+
+    /// The box bounding `ball` at its current size.
+    fn ball_aabb(&self, ball: &Ball) -> Rect {
+        ball.aabb(self.ball_radius())
     }
 
-    fn handle_potential_score(&mut self, x_bound: f32) -> bool {
-        let (left_score, right_score) = &mut self.score;
+    /// Current ball radius, which may grow during a rally.
+    fn ball_radius(&self) -> f32 {
+        Ball::radius_for_rally(self.rally_hits, self.settings.ball_growth) * self.size_scale
+    }
 
-        if self.ball_pos.x - BALL_RADIUS < 0. {
-            *right_score += 1;
-        } else if self.ball_pos.x + BALL_RADIUS > x_bound {
-            *left_score += 1;
+    /// Shrinks paddle and ball sizes to suit a small field, if scaling to the window is on.
+    fn update_size_scale(&mut self, width: f32, height: f32) {
+        self.size_scale = if self.settings.scale_to_window {
+            geometry::small_board_scale(width, height)
         } else {
-            return false;
+            1.
+        };
+    }
+
+    /// Width of both paddles at the current size scale.
+    fn paddle_width(&self) -> f32 {
+        PADDLE_WIDTH * self.size_scale
+    }
+
+    /// Counts a paddle return towards the rally and match stats.
+    fn record_paddle_hit(&mut self, side: Side) {
+        self.push_event(GameEvent::PaddleHit(side));
+        self.rally_hits += 1;
+        self.since_paddle_hit = std::time::Duration::ZERO;
+        self.heat.record_hit();
+        // The grace period only softens the serve itself
+        self.serve_grace = std::time::Duration::ZERO;
+        self.stats.record_paddle_hit(self.rally_hits);
+    }
+
+    /// Records who served the current ball: the serve travels away from the server.
+    fn update_server(&mut self) {
+        self.server = if self.ball.vel.x >= 0. {
+            Side::Left
+        } else {
+            Side::Right
+        };
+    }
+
+    /// Throws away the current round without scoring it and serves a fresh one, unless a
+    /// redo happened too recently.
+    fn redo_round(&mut self, width: f32, height: f32) {
+        if !self.round_reset_cooldown.is_zero() {
+            return;
         }
+        self.round_reset_cooldown = ROUND_RESET_COOLDOWN;
+        self.reset_round(width, height);
+        self.delay_duration = self.serve_delay();
+    }
 
-        return true;
+    /// The pause before a new round's serve: the configured one, or until the server serves if
+    /// they serve on keypress. A zero delay leaves the ball to move on the very next frame.
+    fn serve_delay(&self) -> std::time::Duration {
+        if self.settings.serve_on_keypress {
+            std::time::Duration::MAX
+        } else {
+            self.settings.serve_delay
+        }
     }
-}
 
-impl EventHandler for GameState {
-    fn update(&mut self, ctx: &mut Context) -> GameResult {
-        let delta_time = ctx.time.delta();
+    /// Places a freshly served ball in the middle of the field.
+    fn serve_from_center(&mut self, width: f32, height: f32) {
+        self.ball = self.center_serve(width, height);
+        self.update_server();
+        self.rally_hits = 0;
+        self.since_paddle_hit = std::time::Duration::ZERO;
+        self.heat = HeatMeter::default();
+        self.extra_balls.clear();
+        self.predicted_path.clear();
 
-        // Check for pause input before anything
-        if ctx.keyboard.is_key_just_pressed(KeyCode::Space) {
-            self.paused = !self.paused;
+        // Doubles keeps a second ball in play alongside the main one
+        if self.settings.doubles {
+            let second = self.center_serve(width, height);
+            self.extra_balls.push(second);
         }
+    }
 
-        if self.paused {
-            return Ok(());
+    /// A new ball served from the middle of the field in a random direction.
+    fn center_serve(&mut self, width: f32, height: f32) -> Ball {
+        let center = Point2 {
+            x: width / 2.,
+            y: height / 2.,
+        };
+        Ball::new(
+            center,
+            self.serve_speed(),
+            self.max_serve_angle(),
+            &mut self.rng,
+        )
+    }
+
+    /// How far the beginner assist has eased up, from 0 on the first serve of a match to 1 at
+    /// full difficulty. Always 1 without the assist.
+    fn assist_ramp(&self) -> f32 {
+        if !self.settings.beginner_assist {
+            return 1.;
         }
+        let points = self.score.0 as u32 + self.score.1 as u32;
+        points.min(ASSIST_RAMP_POINTS) as f32 / ASSIST_RAMP_POINTS as f32
+    }
 
-        if self.delay_duration > std::time::Duration::ZERO {
-            self.delay_duration = self
-                .delay_duration
-                .checked_sub(delta_time)
-                .unwrap_or(std::time::Duration::ZERO);
-            return Ok(());
+    /// Speed of a fresh serve, slowed early on by the beginner assist.
+    fn serve_speed(&self) -> f32 {
+        let factor = ASSIST_START_SPEED + (1. - ASSIST_START_SPEED) * self.assist_ramp();
+        self.settings.ball_speed * factor
+    }
+
+    /// Steepest a fresh serve can be, flattened early on by the beginner assist.
+    fn max_serve_angle(&self) -> f32 {
+        let factor = ASSIST_START_ANGLE + (1. - ASSIST_START_ANGLE) * self.assist_ramp();
+        self.settings.max_serve_angle * factor
+    }
+
+    /// X position of the right paddle for a field of the given width.
+    fn right_paddle_x(&self, width: f32) -> f32 {
+        width - self.paddle_width() - self.settings.paddle_margin
+    }
+
+    /// Removes a life from the player defending `side`. Running out ends the match, through
+    /// `check_for_winner`.
+    fn lose_life(&mut self, side: Side) {
+        let lives = side.of_mut(&mut self.lives);
+        *lives = lives.saturating_sub(1);
+    }
+
+    /// Whether the next point could end the match: one more point for either side would
+    /// produce a winner, under whichever win conditions are active.
+    fn is_match_point(&self) -> bool {
+        if self.settings.zen || self.warmup || self.winner().is_some() {
+            return false;
         }
+        [Side::Left, Side::Right].into_iter().any(|scorer| {
+            let mut score = self.score;
+            let mut lives = self.lives;
+            *scorer.of_mut(&mut score) = scorer.of(&score).saturating_add(1);
+            if self.settings.elimination {
+                let breached = scorer.opposite().of_mut(&mut lives);
+                *breached = breached.saturating_sub(1);
+            }
+            self.winner_for(score, lives).is_some()
+        })
+    }
 
-        self.handle_paddle_movement(&ctx.keyboard)?;
+    /// Ends the match in `winner`'s favour, starting the celebration shown before the summary.
+    fn win_match(&mut self, winner: Side) {
+        self.winner = Some(winner);
+        self.records
+            .record_win(GameMode::ALL[self.menu_selection].name(), winner);
+        if self.save_records
+            && let Err(e) = self.records.save(RECORDS_PATH)
+        {
+            log::warn!("Could not save records to {RECORDS_PATH}: {e}");
+        }
+        self.push_event(GameEvent::MatchWon { winner });
+        self.celebration = Some(CELEBRATION_DURATION);
+    }
 
-        let (width, height) = ctx.gfx.drawable_size();
-        self.handle_ball_movement(height)?;
-        if self.handle_potential_score(width) {
-            // Reset ball position and velocity
-            self.ball_pos = Point2 {
-                x: width / 2.,
-                y: height / 2.,
-            };
-            self.ball_vel = Self::random_ball_velocity();
+    /// Takes the frame's input and handles the paddle movement.
+    fn handle_paddle_movement(&mut self, input: &InputFrame, y_bound: f32) -> GameResult {
+        if self.settings.opponent == Opponent::Ai {
+            let radius = self.ball_radius();
+            self.ai.update(
+                &mut self.right_paddle,
+                &self.ball,
+                radius,
+                y_bound,
+                self.settings.ai_miss_chance,
+                &mut self.rng,
+            );
+        }
 
-            // Reset paddles position
-            self.left_paddle_pos = Point2 {
-                x: 20.,
-                y: (height / 2.) - (PADDLE_HEIGHT / 2.),
+        self.pressed_walls = (None, None);
+        for controls in &self.settings.controls {
+            // The AI has the right paddle to itself
+            if controls.side == Side::Right && self.settings.opponent == Opponent::Ai {
+                continue;
+            }
+
+            // Note: origin is TLC, so up is negative. Holding both keys cancels out.
+            let keys = match self.settings.paddle_link {
+                PaddleLink::Independent => input.axis(controls.up, controls.down),
+                PaddleLink::Linked => self
+                    .settings
+                    .controls
+                    .iter()
+                    .map(|controls| input.axis(controls.up, controls.down))
+                    .sum::<f32>()
+                    .clamp(-1., 1.),
             };
-            self.right_paddle_pos = Point2 {
-                x: width - PADDLE_WIDTH - 20.,
-                y: (height / 2.) - (PADDLE_HEIGHT / 2.),
+            let keys = if *controls.side.of(&self.inverted) {
+                -keys
+            } else {
+                keys
             };
+            let pos_change = self.settings.paddle_drive(keys, input.controller_axis);
+
+            let paddle =
+                side_paddle_mut(&mut self.left_paddle, &mut self.right_paddle, controls.side);
+            if input.just_pressed.contains(&controls.dash) {
+                paddle.try_dash();
+            }
+            paddle.pos.y += pos_change * self.settings.paddle_speed * paddle.speed_multiplier();
+            paddle.clamp_to(y_bound);
 
-            // Now add a short pause
-            self.delay_duration = std::time::Duration::from_secs(1);
+            if let Some(wall) = paddle.pressed_wall(pos_change, y_bound) {
+                *controls.side.of_mut(&mut self.pressed_walls) = Some(wall);
+            }
+        }
+
+        for paddle in [&mut self.left_paddle, &mut self.right_paddle] {
+            paddle.tick_dash();
         }
 
         Ok(())
     }
 
-    fn draw(&mut self, ctx: &mut Context) -> GameResult {
-        let mut canvas = graphics::Canvas::from_frame(ctx, Color::BLACK);
+    /// Whether the current server pressed their serve key this frame.
+    /// The AI never keeps anyone waiting.
+    fn serve_key_pressed(&self, input: &InputFrame) -> bool {
+        if self.server == Side::Right && self.settings.opponent == Opponent::Ai {
+            return true;
+        }
+        self.settings.controls.iter().any(|controls| {
+            controls.side == self.server && input.just_pressed.contains(&controls.serve)
+        })
+    }
 
-        // Set up meshes
-        let left_paddle = graphics::Mesh::new_rectangle(
-            ctx,
-            graphics::DrawMode::fill(),
-            Rect::new(
-                self.left_paddle_pos.x,
-                self.left_paddle_pos.y,
-                PADDLE_WIDTH,
-                PADDLE_HEIGHT,
-            ),
-            Color::WHITE,
-        )?;
+    /// Hands the right paddle over between the second player and the AI.
+    /// The AI starts out aiming where the paddle already is, so it doesn't jump.
+    pub fn toggle_opponent(&mut self) {
+        self.settings.opponent = match self.settings.opponent {
+            Opponent::Human => {
+                self.ai = Ai::new(&self.right_paddle, self.settings.paddle_speed);
+                Opponent::Ai
+            }
+            Opponent::Ai => Opponent::Human,
+        };
+    }
 
-        let right_paddle = graphics::Mesh::new_rectangle(
-            ctx,
-            graphics::DrawMode::fill(),
-            Rect::new(
-                self.right_paddle_pos.x,
-                self.right_paddle_pos.y,
-                PADDLE_WIDTH,
-                PADDLE_HEIGHT,
-            ),
-            Color::WHITE,
-        )?;
+    /// In practice mode, lets the arrow keys launch the ball before the delay expires.
+    /// Left/Right picks which side the ball travels towards.
+    fn handle_practice_serve(&mut self, input: &InputFrame) {
+        let x_direction = if input.just_pressed.contains(&KeyCode::Left) {
+            -1.
+        } else if input.just_pressed.contains(&KeyCode::Right) {
+            1.
+        } else {
+            return;
+        };
 
-        let ball = graphics::Mesh::new_circle(
-            ctx,
-            graphics::DrawMode::fill(),
-            Point2 {
-                x: self.ball_pos.x,
-                y: self.ball_pos.y,
-            },
-            BALL_RADIUS,
-            3.0,
-            Color::WHITE,
-        )?;
+        self.ball.vel = Ball::serve_velocity(
+            x_direction,
+            self.serve_speed(),
+            self.max_serve_angle(),
+            &mut self.rng,
+        );
+        self.ball.last_hitter = None;
+        self.update_server();
+        self.predicted_path.clear();
+        self.delay_duration = std::time::Duration::ZERO;
+        self.go_flash = GO_FLASH_DURATION;
+        self.serve_grace = SERVE_GRACE_DURATION;
+    }
 
-        // Draw meshes
-        canvas.draw(&left_paddle, graphics::DrawParam::new());
-        canvas.draw(&right_paddle, graphics::DrawParam::new());
-        canvas.draw(&ball, graphics::DrawParam::new());
+    fn open_settings(&mut self) {
+        self.screen_before_settings = self.screen;
+        self.screen = Screen::Settings;
+    }
 
-        let (width, height) = ctx.gfx.drawable_size();
+    /// Leaves the settings screen, persisting the preferences.
+    fn close_settings(&mut self) {
+        self.config.gameplay = GameplayConfig::from_settings(&self.settings);
+        if let Err(e) = self.config.save(CONFIG_PATH) {
+            log::warn!("Could not save config to {CONFIG_PATH}: {e}");
+        }
+        self.screen = self.screen_before_settings;
+    }
 
-        // Draw score
-        let mut score_text = graphics::Text::new(format!("{} - {}", self.score.0, self.score.1));
-        score_text.set_scale(24.0);
-        let text_measure = score_text.measure(ctx)?;
-        canvas.draw(
-            &score_text,
-            graphics::DrawParam::new()
-                .dest(Point2 {
-                    x: (width / 2.0) - (text_measure.x / 2.0),
-                    y: 20.0,
-                })
-                .color(Color::WHITE),
+    /// Starts a new match with the current settings. This is the only way onto the field.
+    pub fn start_match(&mut self, width: f32, height: f32) {
+        self.score = (0, 0);
+        self.lives = (self.settings.starting_lives, self.settings.starting_lives);
+        self.winner = None;
+        self.celebration = None;
+        self.stats = MatchStats::default();
+        self.recording.clear();
+        self.training.reset();
+        self.comeback = None;
+        self.match_time = std::time::Duration::ZERO;
+        self.paused = false;
+        self.delay_duration = std::time::Duration::ZERO;
+        self.goal_flash = None;
+        self.go_flash = std::time::Duration::ZERO;
+        self.coin_flip = None;
+        // A fresh match always gets a fresh obstacle layout
+        self.obstacles.clear();
+        self.ready = (self.settings.ready_check && self.settings.opponent == Opponent::Human)
+            .then_some((false, false));
+        self.warmup = self.settings.warmup;
+        self.warmup_ready = (false, false);
+        self.warmup_armed = false;
+        self.reset_round(width, height);
+        if let Some(server) = self.first_server.or(self.fair_first_server()) {
+            self.serve_from(server);
+        } else if self.settings.coin_flip {
+            self.flip_coin();
+        }
+        self.screen = Screen::Playing;
+    }
+
+    /// Who serves first under the fair serve tweak: against the AI, the AI serves so the ball
+    /// comes to the human. Two humans are left to the usual first serve choice.
+    fn fair_first_server(&self) -> Option<Side> {
+        (self.settings.fair_serve && self.settings.opponent == Opponent::Ai).then_some(Side::Right)
+    }
+
+    /// Decides the first server with a coin flip, holding the serve while it animates.
+    fn flip_coin(&mut self) {
+        let result = if self.rng.random::<bool>() {
+            Side::Left
+        } else {
+            Side::Right
+        };
+        self.serve_from(result);
+        self.coin_flip = Some((result, COIN_FLIP_DURATION));
+        self.delay_duration = COIN_FLIP_DURATION;
+    }
+
+    /// Re-aims the ball so that `server` serves it.
+    fn serve_from(&mut self, server: Side) {
+        let x_direction = match server {
+            Side::Left => 1.,
+            Side::Right => -1.,
+        };
+        self.ball.vel = Ball::serve_velocity(
+            x_direction,
+            self.serve_speed(),
+            self.max_serve_angle(),
+            &mut self.rng,
         );
+        self.update_server();
+    }
 
-        if self.paused {
-            let mut pause_text = graphics::Text::new("Paused");
-            pause_text.set_scale(30.0);
-            let text_measure = pause_text.measure(ctx)?;
-            canvas.draw(
-                &pause_text,
-                graphics::DrawParam::new()
-                    .dest(Point2 {
-                        x: (width / 2.0) - (text_measure.x / 2.0),
-                        y: (height / 2.0) - (text_measure.y / 2.0),
-                    })
-                    .color(Color::RED),
-            );
+    /// Up/Down picks a mode and Enter starts it. Left/Right picks who serves first, picking the
+    /// same side again goes back to random. B switches the board, which `update` resizes the
+    /// window to.
+    fn handle_menu_input(&mut self, input: &InputFrame, width: f32, height: f32) {
+        if input.just_pressed.contains(&KeyCode::B) {
+            self.settings.board = cycled(&BoardPreset::ALL, self.settings.board, 1);
         }
 
-        canvas.finish(ctx)?;
+        let count = GameMode::ALL.len();
+        if input.just_pressed.contains(&KeyCode::Up) {
+            self.menu_selection = (self.menu_selection + count - 1) % count;
+        }
+        if input.just_pressed.contains(&KeyCode::Down) {
+            self.menu_selection = (self.menu_selection + 1) % count;
+        }
 
-        Ok(())
+        for (key, side) in [(KeyCode::Left, Side::Left), (KeyCode::Right, Side::Right)] {
+            if input.just_pressed.contains(&key) {
+                self.first_server = if self.first_server == Some(side) {
+                    None
+                } else {
+                    Some(side)
+                };
+            }
+        }
+
+        if input.just_pressed.contains(&KeyCode::Return) {
+            GameMode::ALL[self.menu_selection].apply(&mut self.settings);
+            self.start_match(width, height);
+        }
+    }
+
+    /// Resizes the window to the selected board and refits the field to it.
+    fn fit_window_to_board(&mut self, ctx: &mut Context) {
+        let board = self.settings.board;
+        let (width, height) = board.window_size();
+        if let Err(e) = ctx
+            .gfx
+            .set_mode(WindowMode::default().dimensions(width, height))
+        {
+            log::warn!("Could not resize the window for {}: {e}", board.name());
+            return;
+        }
+        let (width, height) = ctx.gfx.drawable_size();
+        self.fit_to_bounds(width, height);
+    }
+
+    /// Up/Down picks an option on the summary screen and Enter confirms it.
+    fn handle_summary_input(&mut self, input: &InputFrame, width: f32, height: f32) {
+        // Holding Left/Right scrubs through the match, faster with Shift held
+        let held = |keys: [KeyCode; 2]| keys.iter().any(|key| input.pressed.contains(key));
+        let speed = if held([KeyCode::LShift, KeyCode::RShift]) {
+            SCRUB_FAST_FRAMES
+        } else {
+            1
+        };
+        let scrub = if input.pressed.contains(&KeyCode::Left) {
+            -speed
+        } else if input.pressed.contains(&KeyCode::Right) {
+            speed
+        } else {
+            0
+        };
+        if scrub != 0
+            && let Some(snapshot) = self.recording.scrub(scrub).cloned()
+        {
+            self.restore(&snapshot);
+        }
+
+        let count = SummaryOption::ALL.len();
+        if input.just_pressed.contains(&KeyCode::Up) {
+            self.summary_selection = (self.summary_selection + count - 1) % count;
+        }
+        if input.just_pressed.contains(&KeyCode::Down) {
+            self.summary_selection = (self.summary_selection + 1) % count;
+        }
+
+        if !input.just_pressed.contains(&KeyCode::Return) {
+            return;
+        }
+        match SummaryOption::ALL[self.summary_selection] {
+            SummaryOption::Rematch => self.start_match(width, height),
+            SummaryOption::ExportBestRally => self.export_best_rally(),
+            SummaryOption::ChangeSettings => self.open_settings(),
+            SummaryOption::MainMenu => self.screen = Screen::Menu,
+        }
+    }
+
+    /// Up/Down picks an element, holding R/G/B raises its channels (lowers them with Shift) and
+    /// Delete goes back to the default colors. Changes show straight away.
+    fn handle_color_picker_input(&mut self, input: &InputFrame) {
+        let count = ThemeElement::ALL.len();
+        if input.just_pressed.contains(&KeyCode::Up) {
+            self.color_picker_selection = (self.color_picker_selection + count - 1) % count;
+        }
+        if input.just_pressed.contains(&KeyCode::Down) {
+            self.color_picker_selection = (self.color_picker_selection + 1) % count;
+        }
+
+        if input.just_pressed.contains(&KeyCode::Delete) {
+            self.config.custom_theme = None;
+            theme::CustomTheme::default().apply(&mut self.theme);
+            return;
+        }
+
+        let amount = if [KeyCode::LShift, KeyCode::RShift]
+            .iter()
+            .any(|key| input.pressed.contains(key))
+        {
+            -COLOR_PICKER_STEP
+        } else {
+            COLOR_PICKER_STEP
+        };
+        let element = ThemeElement::ALL[self.color_picker_selection];
+        for (channel, key) in [KeyCode::R, KeyCode::G, KeyCode::B].into_iter().enumerate() {
+            if input.pressed.contains(&key) {
+                let custom_theme = self.config.custom_theme.get_or_insert_default();
+                custom_theme.adjust(element, channel, amount);
+                custom_theme.apply(&mut self.theme);
+            }
+        }
+    }
+
+    /// Up/Down picks a settings row and Left/Right adjusts it.
+    /// Audio changes are applied to the playing sources by `update` straight away.
+    fn handle_settings_input(&mut self, input: &InputFrame, width: f32, height: f32) {
+        if input.just_pressed.contains(&KeyCode::C) {
+            self.screen = Screen::ColorPicker;
+            return;
+        }
+        if input.just_pressed.contains(&KeyCode::Up) {
+            self.settings_selection =
+                (self.settings_selection + SettingsItem::ALL.len() - 1) % SettingsItem::ALL.len();
+        }
+        if input.just_pressed.contains(&KeyCode::Down) {
+            self.settings_selection = (self.settings_selection + 1) % SettingsItem::ALL.len();
+        }
+
+        let steps = if input.just_pressed.contains(&KeyCode::Left) {
+            -1.
+        } else if input.just_pressed.contains(&KeyCode::Right) {
+            1.
+        } else {
+            return;
+        };
+
+        let audio_settings = &mut self.config.audio;
+        match SettingsItem::ALL[self.settings_selection] {
+            SettingsItem::BallSpeed => self.settings.adjust_ball_speed(steps),
+            SettingsItem::ServeAngle => self.settings.adjust_max_serve_angle(steps),
+            SettingsItem::SoundEffects => audio_settings.sfx_enabled = !audio_settings.sfx_enabled,
+            SettingsItem::Music => audio_settings.music_enabled = !audio_settings.music_enabled,
+            SettingsItem::Volume => audio_settings.adjust_volume(steps),
+            SettingsItem::SpeedPitch => audio_settings.speed_pitch = !audio_settings.speed_pitch,
+            SettingsItem::Background => {
+                self.theme.background_style = cycled(
+                    &BackgroundStyle::ALL,
+                    self.theme.background_style,
+                    steps as i32,
+                )
+            }
+            SettingsItem::HitSpeed => {
+                self.settings.hit_speed =
+                    cycled(&HitSpeed::ALL, self.settings.hit_speed, steps as i32)
+            }
+            SettingsItem::ServeGrace => self.settings.serve_grace = !self.settings.serve_grace,
+            SettingsItem::MatchPointSlowMo => {
+                self.settings.match_point_slow_mo = !self.settings.match_point_slow_mo
+            }
+            SettingsItem::BeginnerAssist => {
+                self.settings.beginner_assist = !self.settings.beginner_assist
+            }
+            SettingsItem::DeadBallTimeout => self.settings.cycle_dead_ball_timeout(steps as i32),
+            SettingsItem::MultiBall => self.settings.multi_ball = !self.settings.multi_ball,
+            SettingsItem::Doubles => self.settings.doubles = !self.settings.doubles,
+            SettingsItem::TrainingTargets => {
+                self.settings.training_targets = !self.settings.training_targets
+            }
+            SettingsItem::Practice => self.settings.practice = !self.settings.practice,
+            SettingsItem::ServeOnKeypress => {
+                self.settings.serve_on_keypress = !self.settings.serve_on_keypress
+            }
+            SettingsItem::ServeDelay => self.settings.cycle_serve_delay(steps as i32),
+            SettingsItem::Comeback => self.settings.cycle_comeback_margin(steps as i32),
+            SettingsItem::MercyBall => self.settings.cycle_mercy_ball(steps as i32),
+            SettingsItem::CoinFlip => self.settings.coin_flip = !self.settings.coin_flip,
+            SettingsItem::AiMissChance => self.settings.adjust_ai_miss_chance(steps),
+            SettingsItem::PaddleLink => {
+                self.settings.paddle_link = self.settings.paddle_link.toggled()
+            }
+            SettingsItem::Sensitivity => self.settings.adjust_sensitivity(steps),
+            SettingsItem::KeyboardSensitivity => {
+                self.settings.keyboard_sensitivity = !self.settings.keyboard_sensitivity
+            }
+            SettingsItem::HeatMeter => self.settings.heat_meter = !self.settings.heat_meter,
+            SettingsItem::ServeRule => {
+                self.settings.serve_rule =
+                    cycled(&ServeRule::ALL, self.settings.serve_rule, steps as i32)
+            }
+            SettingsItem::MirrorView => self.settings.mirror_view = !self.settings.mirror_view,
+            SettingsItem::Restitution => self.settings.adjust_restitution(steps),
+            SettingsItem::SpectatorHud => {
+                self.config.display.spectator_hud = !self.config.display.spectator_hud
+            }
+            SettingsItem::SplitScore => {
+                self.theme.score_layout.split = !self.theme.score_layout.split
+            }
+            SettingsItem::ScaleToWindow => {
+                self.settings.scale_to_window = !self.settings.scale_to_window
+            }
+            SettingsItem::FairServe => self.settings.fair_serve = !self.settings.fair_serve,
+            SettingsItem::ReadyCheck => self.settings.ready_check = !self.settings.ready_check,
+            SettingsItem::Warmup => self.settings.warmup = !self.settings.warmup,
+            SettingsItem::Obstacles => {
+                self.settings.obstacle_count = (self.settings.obstacle_count as i32 + steps as i32)
+                    .rem_euclid(MAX_OBSTACLES as i32 + 1)
+                    as u8
+            }
+            SettingsItem::FixedObstacles => {
+                self.settings.fixed_obstacles = !self.settings.fixed_obstacles
+            }
+            SettingsItem::Wall(edge) => {
+                let behavior = edge.behavior_mut(&mut self.settings.walls);
+                *behavior = cycled(&WallBehavior::ALL, *behavior, steps as i32);
+            }
+            SettingsItem::Net => self.settings.adjust_net_height(steps),
+            SettingsItem::SpeedBurst => self.settings.speed_burst = !self.settings.speed_burst,
+            SettingsItem::PowerUps => self.settings.cycle_power_up_interval(steps as i32),
+            SettingsItem::PaddleMargin => self.settings.adjust_paddle_margin(steps),
+            SettingsItem::PaddleHeight(side) => self.settings.adjust_paddle_height(side, steps),
+            SettingsItem::BallGrowth => self.settings.ball_growth = !self.settings.ball_growth,
+            SettingsItem::MoveDuringDelay => {
+                self.settings.move_during_delay = !self.settings.move_during_delay
+            }
+            SettingsItem::WinningScore => self.settings.cycle_winning_score(steps as i32),
+            SettingsItem::WinByTwo => self.settings.win_by_two = !self.settings.win_by_two,
+        }
+        // Paddle changes show on the field straight away
+        self.fit_to_bounds(width, height);
+    }
+
+    fn settings_item_label(&self, item: SettingsItem) -> String {
+        let on_off = |enabled: bool| if enabled { "On" } else { "Off" };
+        match item {
+            SettingsItem::BallSpeed => format!("Ball speed: {:.1}", self.settings.ball_speed),
+            SettingsItem::ServeAngle => format!(
+                "Serve angle: up to {:.0}°",
+                self.settings.max_serve_angle.to_degrees()
+            ),
+            SettingsItem::SoundEffects => {
+                format!("Sound effects: {}", on_off(self.config.audio.sfx_enabled))
+            }
+            SettingsItem::Music => format!("Music: {}", on_off(self.config.audio.music_enabled)),
+            SettingsItem::Volume => {
+                format!("Volume: {:.0}%", self.config.audio.master_volume * 100.)
+            }
+            SettingsItem::SpeedPitch => format!(
+                "Speed-pitched bounces: {}",
+                on_off(self.config.audio.speed_pitch)
+            ),
+            SettingsItem::Background => {
+                format!("Background: {}", self.theme.background_style.name())
+            }
+            SettingsItem::HitSpeed => format!("Hit speed: {}", self.settings.hit_speed.name()),
+            SettingsItem::MatchPointSlowMo => format!(
+                "Match point slow-mo: {}",
+                on_off(self.settings.match_point_slow_mo)
+            ),
+            SettingsItem::BeginnerAssist => {
+                format!("Beginner assist: {}", on_off(self.settings.beginner_assist))
+            }
+            SettingsItem::ServeGrace => {
+                format!("Serve grace: {}", on_off(self.settings.serve_grace))
+            }
+            SettingsItem::DeadBallTimeout => match self.settings.dead_ball_timeout {
+                Some(timeout) => format!("Dead-ball timeout: {}s", timeout.as_secs()),
+                None => "Dead-ball timeout: Off".to_string(),
+            },
+            SettingsItem::MultiBall => format!("Multi-ball: {}", on_off(self.settings.multi_ball)),
+            SettingsItem::Doubles => format!("Doubles: {}", on_off(self.settings.doubles)),
+            SettingsItem::TrainingTargets => format!(
+                "Training targets: {}",
+                on_off(self.settings.training_targets)
+            ),
+            SettingsItem::Practice => format!("Practice: {}", on_off(self.settings.practice)),
+            SettingsItem::ServeOnKeypress => format!(
+                "Serve on keypress: {}",
+                on_off(self.settings.serve_on_keypress)
+            ),
+            SettingsItem::ServeDelay => {
+                if self.settings.serve_delay.is_zero() {
+                    "Serve delay: None".to_string()
+                } else {
+                    format!("Serve delay: {}s", self.settings.serve_delay.as_secs_f32())
+                }
+            }
+            SettingsItem::Comeback => match self.settings.comeback_margin {
+                Some(margin) => format!("Comeback paddle: {margin} behind"),
+                None => "Comeback paddle: Off".to_string(),
+            },
+            SettingsItem::MercyBall => match self.settings.mercy_ball {
+                Some(deficit) => format!("Mercy ball: {deficit} behind"),
+                None => "Mercy ball: Off".to_string(),
+            },
+            SettingsItem::CoinFlip => format!("Coin flip: {}", on_off(self.settings.coin_flip)),
+            SettingsItem::AiMissChance => {
+                format!(
+                    "AI miss chance: {:.0}%",
+                    self.settings.ai_miss_chance * 100.
+                )
+            }
+            SettingsItem::PaddleLink => {
+                format!("Paddles: {}", self.settings.paddle_link.name())
+            }
+            SettingsItem::PaddleMargin => {
+                format!("Paddle margin: {:.0}px", self.settings.paddle_margin)
+            }
+            SettingsItem::PaddleHeight(side) => format!(
+                "{} paddle height: {:.0}px",
+                side.name(),
+                self.settings.paddle_height(side)
+            ),
+            SettingsItem::MoveDuringDelay => format!(
+                "Move during delay: {}",
+                on_off(self.settings.move_during_delay)
+            ),
+            SettingsItem::BallGrowth => {
+                format!("Growing ball: {}", on_off(self.settings.ball_growth))
+            }
+            SettingsItem::WinningScore => match self.settings.winning_score {
+                Some(score) => format!("Winning score: {score}"),
+                None => "Winning score: None".to_string(),
+            },
+            SettingsItem::WinByTwo => format!("Win by two: {}", on_off(self.settings.win_by_two)),
+            SettingsItem::Sensitivity => {
+                format!("Stick sensitivity: {:.2}x", self.settings.sensitivity)
+            }
+            SettingsItem::KeyboardSensitivity => format!(
+                "Sensitivity on keyboard: {}",
+                on_off(self.settings.keyboard_sensitivity)
+            ),
+            SettingsItem::HeatMeter => format!("Heat meter: {}", on_off(self.settings.heat_meter)),
+            SettingsItem::ServeRule => format!("Serve: {}", self.settings.serve_rule.name()),
+            SettingsItem::MirrorView => {
+                format!("Mirror view: {}", on_off(self.settings.mirror_view))
+            }
+            SettingsItem::Restitution => {
+                format!("Bounciness: {:.0}%", self.settings.restitution * 100.)
+            }
+            SettingsItem::SpectatorHud => format!(
+                "Spectator HUD: {}",
+                on_off(self.config.display.spectator_hud)
+            ),
+            SettingsItem::SplitScore => {
+                format!("Split score: {}", on_off(self.theme.score_layout.split))
+            }
+            SettingsItem::ScaleToWindow => format!(
+                "Scale to small windows: {}",
+                on_off(self.settings.scale_to_window)
+            ),
+            SettingsItem::FairServe => format!("Fair serve: {}", on_off(self.settings.fair_serve)),
+            SettingsItem::ReadyCheck => {
+                format!("Ready check: {}", on_off(self.settings.ready_check))
+            }
+            SettingsItem::Warmup => format!("Warmup rally: {}", on_off(self.settings.warmup)),
+            SettingsItem::Obstacles => match self.settings.obstacle_count {
+                0 => "Obstacles: Off".to_string(),
+                count => format!("Obstacles: {count}"),
+            },
+            SettingsItem::FixedObstacles => {
+                if self.settings.fixed_obstacles {
+                    "Obstacle layout: Fixed per match".to_string()
+                } else {
+                    "Obstacle layout: New each round".to_string()
+                }
+            }
+            SettingsItem::Wall(edge) => format!(
+                "{} wall: {}",
+                edge.name(),
+                edge.behavior(&self.settings.walls).name()
+            ),
+            SettingsItem::Net => {
+                if self.settings.net_height > 0. {
+                    format!("Net: {:.0}px high", self.settings.net_height)
+                } else {
+                    "Net: Off".to_string()
+                }
+            }
+            SettingsItem::SpeedBurst => {
+                format!("Speed burst zone: {}", on_off(self.settings.speed_burst))
+            }
+            SettingsItem::PowerUps => match self.settings.power_up_interval {
+                Some(interval) => format!("Power-ups: Every {}s", interval.as_secs()),
+                None => "Power-ups: Off".to_string(),
+            },
+        }
+    }
+
+    /// Handles the ball movement.
+    /// Ball moves and may collide with the paddles.
+    fn handle_ball_movement(&mut self, x_bound: f32, y_bound: f32) -> GameResult {
+        let mut ball = self.ball;
+        let speed_multiplier = self.serve_speed_multiplier() * self.time_scale;
+        self.move_ball(&mut ball, speed_multiplier, x_bound, y_bound);
+        self.ball = ball;
+
+        let mut extra_balls = std::mem::take(&mut self.extra_balls);
+        for ball in &mut extra_balls {
+            self.move_ball(ball, self.time_scale, x_bound, y_bound);
+        }
+        self.extra_balls = extra_balls;
+
+        self.check_stuck_ball();
+
+        Ok(())
+    }
+
+    /// Moves `ball` by one frame, bouncing it off the paddles and walls.
+    fn move_ball(&mut self, ball: &mut Ball, speed_multiplier: f32, x_bound: f32, y_bound: f32) {
+        // Do basic movement
+        let previous_pos = ball.pos;
+        let previous_x = ball.pos.x;
+        ball.pos.x += ball.vel.x * speed_multiplier;
+        ball.pos.y += ball.vel.y * speed_multiplier;
+        ball.vel.y += ball.arc * speed_multiplier;
+
+        let speed = ball.speed();
+        let radius = self.ball_radius();
+        let ball_box = self.ball_aabb(ball);
+        let previous_box = Rect {
+            x: ball_box.x - (ball.pos.x - previous_x),
+            ..ball_box
+        };
+
+        let left_box = self.left_paddle.rect();
+        let left_surface_x = left_box.right();
+        let right_box = self.right_paddle.rect();
+        let right_surface_x = right_box.left();
+
+        // Only a ball heading towards a paddle that was still in front of its surface last frame
+        // can bounce. One that has already got behind it carries on to the goal.
+        let left_contact = (ball.vel.x < 0.
+            && previous_box.left() >= left_surface_x
+            && intersects(ball_box, left_box))
+        .then(|| (ball.pos.x - left_surface_x).abs());
+        let right_contact = (ball.vel.x > 0.
+            && previous_box.right() <= right_surface_x
+            && intersects(ball_box, right_box))
+        .then(|| (right_surface_x - ball.pos.x).abs());
+
+        // Bounce off the face of the paddle that was touched or, when the ball only clips its
+        // top or bottom, off the corner. At most one paddle is resolved per frame, so a ball
+        // touching both on a tiny field can't have its first bounce overwritten.
+        match nearest_contact(left_contact, right_contact) {
+            Some(Side::Left) => {
+                let distance_from_center = ball.pos.y - self.left_paddle.center_y();
+                let angle = distance_from_center / (self.left_paddle.height / 2.);
+                if angle.abs() <= 1. {
+                    self.record_paddle_hit(Side::Left);
+                    ball.last_hitter = Some(Side::Left);
+                    ball.vel = Point2 {
+                        x: angle.cos(),
+                        y: angle.sin(),
+                    };
+                    ball.set_speed(self.speed_after_hit(speed, angle));
+                } else if let Some(corner) =
+                    self.left_paddle
+                        .corner_touching(left_surface_x, ball.pos, radius)
+                {
+                    self.record_paddle_hit(Side::Left);
+                    ball.last_hitter = Some(Side::Left);
+                    ball.bounce_off_corner(corner, 1.);
+                    ball.set_speed(self.settings.speed_after_bounce(speed));
+                }
+            }
+            Some(Side::Right) => {
+                let distance_from_center = ball.pos.y - self.right_paddle.center_y();
+                let angle = distance_from_center / (self.right_paddle.height / 2.);
+                if angle.abs() <= 1. {
+                    self.record_paddle_hit(Side::Right);
+                    ball.last_hitter = Some(Side::Right);
+                    // Flip x velocity since it's coming from the right
+                    ball.vel = Point2 {
+                        x: -angle.cos(),
+                        y: angle.sin(),
+                    };
+                    ball.set_speed(self.speed_after_hit(speed, angle));
+                } else if let Some(corner) =
+                    self.right_paddle
+                        .corner_touching(right_surface_x, ball.pos, radius)
+                {
+                    self.record_paddle_hit(Side::Right);
+                    ball.last_hitter = Some(Side::Right);
+                    ball.bounce_off_corner(corner, -1.);
+                    ball.set_speed(self.settings.speed_after_bounce(speed));
+                }
+            }
+            None => {}
+        }
+
+        // Bounce off the first obstacle the ball ran into
+        let previous_full_box = Rect {
+            y: ball_box.y - (ball.pos.y - previous_pos.y),
+            ..previous_box
+        };
+        let hit_obstacle = self.obstacles.iter().chain(&self.net).any(|obstacle| {
+            obstacles::bounce_off_obstacle(
+                ball,
+                previous_pos,
+                previous_full_box,
+                ball_box,
+                *obstacle,
+            )
+        });
+        if hit_obstacle {
+            self.push_event(GameEvent::WallBounce);
+        }
+
+        // A ball entering the burst zone gets a single boost, never past the fastest hit speed
+        if let Some(zone) = self.burst_zone
+            && intersects(ball_box, zone)
+            && !intersects(previous_full_box, zone)
+        {
+            let speed = ball.speed();
+            ball.set_speed(
+                (speed * BURST_SPEED_FACTOR).min(self.settings.max_hit_speed.max(speed)),
+            );
+        }
+
+        // Bounce off or wrap through the walls. Walls that score are left to
+        // `handle_potential_score`.
+        let ball_box = self.ball_aabb(ball);
+        for edge in walls::edges_reached(ball_box, x_bound, y_bound) {
+            match edge.behavior(&self.settings.walls) {
+                WallBehavior::Bounce => {
+                    match edge {
+                        Edge::Top | Edge::Bottom => ball.vel.y = -ball.vel.y,
+                        Edge::Left => ball.vel.x = ball.vel.x.abs(),
+                        Edge::Right => ball.vel.x = -ball.vel.x.abs(),
+                    }
+                    ball.set_speed(self.settings.speed_after_bounce(ball.speed()));
+                    self.push_event(GameEvent::WallBounce);
+                }
+                WallBehavior::Wrap => walls::wrap(&mut ball.pos, edge, x_bound, y_bound),
+                WallBehavior::Score => {}
+            }
+        }
+
+        // Never let a bad bounce make the ball vanish
+        ball.recover_if_non_finite();
+    }
+
+    /// How much of its velocity a freshly served ball moves with, ramping up to 1 over the
+    /// serve grace period when it's enabled.
+    pub fn serve_speed_multiplier(&self) -> f32 {
+        if !self.settings.serve_grace {
+            return 1.;
+        }
+        let remaining = self.serve_grace.as_secs_f32() / SERVE_GRACE_DURATION.as_secs_f32();
+        1. - (1. - SERVE_GRACE_START_SPEED) * remaining
+    }
+
+    /// The ball's speed after a paddle hit `offset` away from the paddle's center.
+    fn speed_after_hit(&self, speed: f32, offset: f32) -> f32 {
+        let speed = self.settings.hit_speed.speed_after_hit(
+            speed,
+            offset,
+            self.settings.min_hit_speed,
+            self.settings.max_hit_speed,
+        );
+        self.settings.speed_after_bounce(speed)
+    }
+
+    /// Watchdog for a ball that drifts purely vertically, which would never end the point.
+    /// After a while it gets a horizontal nudge, keeping its speed.
+    fn check_stuck_ball(&mut self) {
+        if self.ball.vel.x.abs() >= STUCK_BALL_MIN_X_SPEED {
+            self.stuck_frames = 0;
+            return;
+        }
+
+        self.stuck_frames += 1;
+        if self.stuck_frames < STUCK_BALL_FRAME_LIMIT {
+            return;
+        }
+
+        log::warn!("Ball stuck with velocity {:?}, nudging it", self.ball.vel);
+        let speed = self.ball.speed();
+        let x_direction = if self.ball.vel.x < 0. { -1. } else { 1. };
+        self.ball.vel.x = x_direction * speed.max(STUCK_BALL_MIN_X_SPEED);
+        self.ball.set_speed(speed);
+        self.stuck_frames = 0;
+    }
+
+    /// Returns who scored, if anyone.
+    fn handle_potential_score(&mut self, ball: Ball, x_bound: f32, y_bound: f32) -> Option<Side> {
+        // Zen mode and the warmup never keep score
+        if self.settings.zen || self.warmup {
+            return None;
+        }
+
+        let edge = walls::edges_reached(self.ball_aabb(&ball), x_bound, y_bound)
+            .find(|edge| edge.behavior(&self.settings.walls) == WallBehavior::Score)?;
+        let scorer = walls::scorer(edge, ball.last_hitter, self.server);
+
+        let points = self.heat.take_points();
+        let points = if self.settings.heat_meter { points } else { 1 };
+        let score = scorer.of_mut(&mut self.score);
+        *score = score.saturating_add(points);
+        Some(scorer)
+    }
+
+    /// Advances the ball by one frame and handles any resulting score.
+    pub fn step(&mut self, width: f32, height: f32) -> GameResult {
+        let hits_before = self.rally_hits;
+        self.handle_ball_movement(width, height)?;
+        self.collect_power_ups(width, height);
+
+        // Safety net: a ball flung far out of the field without scoring is served again
+        if !self.ball.is_within(width, height, ESCAPE_MARGIN) {
+            log::warn!("Ball escaped the field at {:?}, re-serving", self.ball.pos);
+            self.serve_from_center(width, height);
+            return Ok(());
+        }
+        self.extra_balls.retain(|ball| {
+            let within = ball.is_within(width, height, ESCAPE_MARGIN);
+            if !within {
+                log::warn!(
+                    "Extra ball escaped the field at {:?}, removing it",
+                    ball.pos
+                );
+            }
+            within
+        });
+
+        // Practice mode shows where the ball is headed, which only changes on a paddle hit
+        if self.settings.practice
+            && (self.rally_hits != hits_before || self.predicted_path.is_empty())
+        {
+            let target_x = if self.ball.vel.x > 0. { width } else { 0. };
+            self.predicted_path =
+                ai::predict_ball_path(&self.ball, target_x, self.ball_radius(), height);
+        }
+
+        // Every few paddle hits in a rally bring another ball into play
+        if self.settings.multi_ball
+            && self.rally_hits != hits_before
+            && self.rally_hits.is_multiple_of(MULTI_BALL_SPAWN_HITS)
+        {
+            self.spawn_extra_ball(width, height);
+        }
+
+        // In zen mode and the warmup a ball leaving the field is simply served again
+        if (self.settings.zen || self.warmup) && (self.ball.pos.x < 0. || self.ball.pos.x > width) {
+            self.serve_from_center(width, height);
+            return Ok(());
+        }
+
+        // In doubles each ball is served again as soon as it scores, leaving the other in flight
+        if self.settings.doubles {
+            if let Some(scorer) = self.handle_potential_score(self.ball, width, height) {
+                self.award_point(scorer, self.ball.pos.y);
+                self.ball = self.center_serve(width, height);
+                self.update_server();
+                self.predicted_path.clear();
+            }
+        } else if let Some(scorer) = self.handle_potential_score(self.ball, width, height) {
+            let last_server = self.server;
+            self.award_point(scorer, self.ball.pos.y);
+            self.reset_round(width, height);
+            let serve_rule = if self.settings.fair_serve {
+                ServeRule::ToLoser
+            } else {
+                self.settings.serve_rule
+            };
+            if let Some(server) = serve_rule.next_server(last_server, scorer) {
+                self.serve_from(server);
+            }
+            self.maybe_serve_mercy_ball(scorer);
+
+            self.delay_duration = self.serve_delay();
+            return Ok(());
+        }
+
+        // Extra balls score as they leave the field, but play carries on with the rest
+        let mut remaining = Vec::new();
+        for ball in std::mem::take(&mut self.extra_balls) {
+            if let Some(scorer) = self.handle_potential_score(ball, width, height) {
+                self.award_point(scorer, ball.pos.y);
+                if self.settings.doubles {
+                    remaining.push(self.center_serve(width, height));
+                }
+            } else if ball.is_within(width, height, 0.) {
+                remaining.push(ball);
+            }
+        }
+        self.extra_balls = remaining;
+
+        Ok(())
+    }
+
+    /// Flashes the breached goal and records a point for `scorer`, scored by a ball at `ball_y`.
+    fn award_point(&mut self, scorer: Side, ball_y: f32) {
+        // Shots into the right wall count towards training accuracy
+        if self.settings.training_targets && scorer == Side::Left {
+            self.training.record_shot(ball_y);
+        }
+
+        let breached = scorer.opposite();
+        self.goal_flash = Some((breached, GOAL_FLASH_DURATION));
+
+        self.push_event(GameEvent::Scored(scorer));
+        self.stats
+            .record_point(scorer, self.match_time.as_secs_f32(), self.score);
+
+        if self.settings.elimination {
+            self.lose_life(breached);
+        }
+        self.check_for_winner();
+
+        self.update_comeback(scorer);
+    }
+
+    /// Grants the comeback paddle to a player who just fell far enough behind, and takes it
+    /// away once they score or the gap closes. Takes effect when the paddles are next reset.
+    fn update_comeback(&mut self, scorer: Side) {
+        let Some(margin) = self.settings.comeback_margin else {
+            self.comeback = None;
+            return;
+        };
+
+        let trailing = scorer.opposite();
+        let deficit = scorer
+            .of(&self.score)
+            .saturating_sub(*trailing.of(&self.score));
+        if self.comeback == Some(scorer) || deficit < margin {
+            self.comeback = None;
+        }
+        if deficit >= margin {
+            self.comeback = Some(trailing);
+        }
+    }
+
+    /// Sometimes swaps the ball just served for a mercy ball, once the player `scorer` just
+    /// scored against is far enough behind.
+    fn maybe_serve_mercy_ball(&mut self, scorer: Side) {
+        let Some(deficit) = self.settings.mercy_ball else {
+            return;
+        };
+        let trailing = scorer.opposite();
+        let behind = scorer
+            .of(&self.score)
+            .saturating_sub(*trailing.of(&self.score));
+        if behind >= deficit && self.rng.random_bool(MERCY_BALL_CHANCE) {
+            self.ball = self.ball.into_mercy();
+        }
+    }
+
+    /// Takes any power-up a ball has run into off the field and applies it.
+    fn collect_power_ups(&mut self, width: f32, height: f32) {
+        let radius = self.ball_radius();
+        for power_up in std::mem::take(&mut self.power_ups) {
+            let collector = std::iter::once(&self.ball)
+                .chain(&self.extra_balls)
+                .position(|ball| intersects(ball.aabb(radius), power_up.rect));
+            match collector {
+                Some(index) => self.apply_power_up(power_up.kind, index, width, height),
+                None => self.power_ups.push(power_up),
+            }
+        }
+    }
+
+    /// Applies a `kind` power-up collected by the ball at `ball_index` (0 for the main ball,
+    /// then the extra balls in order) on behalf of whoever last hit it. Effects on a player
+    /// are lost if nobody has hit the ball yet.
+    fn apply_power_up(&mut self, kind: PowerUpKind, ball_index: usize, width: f32, height: f32) {
+        let ball = match ball_index {
+            0 => &mut self.ball,
+            index => &mut self.extra_balls[index - 1],
+        };
+        let collector = ball.last_hitter;
+        match kind {
+            PowerUpKind::SpeedUp => {
+                ball.set_speed(ball.speed() * powerups::POWER_UP_SPEED_FACTOR);
+            }
+            PowerUpKind::MultiBall => self.spawn_extra_ball(width, height),
+            PowerUpKind::Grow => {
+                if let Some(side) = collector {
+                    let paddle =
+                        side_paddle_mut(&mut self.left_paddle, &mut self.right_paddle, side);
+                    paddle.height = (paddle.height + powerups::POWER_UP_HEIGHT_CHANGE).min(height);
+                }
+            }
+            PowerUpKind::Shrink => {
+                if let Some(side) = collector {
+                    let paddle = side_paddle_mut(
+                        &mut self.left_paddle,
+                        &mut self.right_paddle,
+                        side.opposite(),
+                    );
+                    paddle.height = (paddle.height - powerups::POWER_UP_HEIGHT_CHANGE)
+                        .max(powerups::MIN_POWERED_PADDLE_HEIGHT.min(paddle.height));
+                }
+            }
+            PowerUpKind::Invert => {
+                if let Some(side) = collector {
+                    *side.opposite().of_mut(&mut self.inverted) = true;
+                }
+            }
+        }
+    }
+
+    /// Height of the paddle on `side`, including any comeback bonus and size scaling.
+    fn paddle_height(&self, side: Side) -> f32 {
+        let base = self.settings.paddle_height(side);
+        let height = if self.comeback == Some(side) {
+            base + self.settings.comeback_height_bonus
+        } else {
+            base
+        };
+        height * self.size_scale
+    }
+
+    /// Serves an extra ball from the center, unless the multi-ball cap is reached.
+    fn spawn_extra_ball(&mut self, width: f32, height: f32) {
+        if self.balls_in_play() >= self.settings.max_balls {
+            return;
+        }
+        let ball = self.center_serve(width, height);
+        self.extra_balls.push(ball);
+    }
+
+    /// Queues `event`, stamped with the current match time.
+    fn push_event(&mut self, event: GameEvent) {
+        self.events.push(TimedEvent {
+            event,
+            time: self.match_time,
+        });
+    }
+
+    /// How many balls are currently on the field.
+    pub fn balls_in_play(&self) -> usize {
+        1 + self.extra_balls.len()
+    }
+
+    /// Plays the sounds for the events raised this frame.
+    #[cfg(feature = "audio")]
+    fn play_sounds(&mut self, ctx: &mut Context) {
+        for event in self.take_events() {
+            if let Some(audio) = &mut self.audio {
+                audio.play_event(ctx, event.event, self.ball.speed(), &self.config.audio);
+            }
+        }
+    }
+
+    /// Without audio the frame's events are simply dropped.
+    #[cfg(not(feature = "audio"))]
+    fn play_sounds(&mut self, _ctx: &mut Context) {
+        self.events.clear();
+    }
+
+    /// Pauses when the controller is unplugged and resumes once it's back.
+    pub fn handle_controller_connection(&mut self, connected: bool) {
+        if self.controller_connected && !connected {
+            self.paused = true;
+            self.controller_lost = true;
+        } else if connected && self.controller_lost {
+            self.paused = false;
+            self.controller_lost = false;
+        }
+        self.controller_connected = connected;
+    }
+
+    /// Hands a frame's input to whatever has the keyboard: the console, the settings, color
+    /// picker, menu or summary screen, or else the match controls. Paddles are driven from the
+    /// same input by `simulate_frame`. Needs no `Context`, so it can be driven headlessly too;
+    /// `update` picks up any window or audio changes afterwards.
+    pub fn handle_input(&mut self, input: &InputFrame, width: f32, height: f32) {
+        if [KeyCode::F12, KeyCode::Snapshot]
+            .iter()
+            .any(|key| input.just_pressed.contains(key))
+        {
+            self.screenshot_requested = true;
+        }
+
+        // The console takes over the keyboard, and halts play, while it's open
+        if input.just_pressed.contains(&KeyCode::Grave) {
+            self.console.open = !self.console.open;
+            self.console.input.clear();
+        }
+        if self.console.open {
+            if input.just_pressed.contains(&KeyCode::Return) {
+                self.console.submit(&mut self.settings);
+            }
+            if input.just_pressed.contains(&KeyCode::Back) {
+                self.console.input.pop();
+            }
+            return;
+        }
+
+        // The settings screen can be opened/closed at any time and halts play while open
+        if input.just_pressed.contains(&KeyCode::Tab) {
+            if self.screen == Screen::Settings {
+                self.close_settings();
+            } else if self.screen == Screen::ColorPicker {
+                self.screen = Screen::Settings;
+            } else {
+                self.open_settings();
+            }
+        }
+
+        match self.screen {
+            Screen::Settings => self.handle_settings_input(input, width, height),
+            Screen::ColorPicker => self.handle_color_picker_input(input),
+            Screen::Menu => self.handle_menu_input(input, width, height),
+            Screen::Summary => self.handle_summary_input(input, width, height),
+            // The celebration of the winning point takes no input
+            Screen::Playing if self.celebration.is_none() => self.handle_match_controls(input),
+            Screen::Playing => {}
+        }
+    }
+
+    /// Applies the match controls that sit outside the simulation: pausing, and while paused,
+    /// handing the right paddle over and stepping through the rewind history.
+    fn handle_match_controls(&mut self, input: &InputFrame) {
+        self.handle_controller_connection(input.controller_connected);
+
+        // Check for pause input before anything. A lost controller keeps the game paused.
+        if input.just_pressed.contains(&KeyCode::Space) && !self.controller_lost {
+            self.paused = !self.paused;
+        }
+
+        if self.paused {
+            if input.just_pressed.contains(&KeyCode::A) {
+                self.toggle_opponent();
+            }
+            self.handle_rewind_input(input);
+        } else if let Some(snapshot) = self.rewind.as_mut().and_then(Rewind::resume).cloned() {
+            // A rewound frame is only partly restored, so play picks up from the newest one
+            self.restore(&snapshot);
+        }
+
+        if input.just_pressed.contains(&KeyCode::G)
+            && let Some(visible) = &mut self.debug_grid
+        {
+            *visible = !*visible;
+        }
+    }
+
+    /// Runs one frame of input handling and simulation.
+    fn update_frame(&mut self, ctx: &mut Context, input: &InputFrame) -> GameResult {
+        let delta_time = ctx.time.delta();
+
+        // Not every platform sends a resize when minimizing, so the drawable size is checked
+        // every frame too
+        let (width, height) = ctx.gfx.drawable_size();
+        self.handle_minimize(width, height);
+        if self.minimized.is_some() {
+            return Ok(());
+        }
+
+        #[cfg(feature = "audio")]
+        let audio_settings = self.config.audio.clone();
+        let board = self.settings.board;
+        self.handle_input(input, width, height);
+        if self.settings.board != board {
+            self.fit_window_to_board(ctx);
+        }
+        #[cfg(feature = "audio")]
+        if self.config.audio != audio_settings
+            && let Some(audio) = &mut self.audio
+        {
+            audio.apply_settings(ctx, &self.config.audio);
+        }
+
+        // Play is halted while the console or another screen is up
+        if self.console.open || self.screen != Screen::Playing {
+            return Ok(());
+        }
+
+        // Play stops while the winning point is celebrated, then the summary is shown
+        if let Some(remaining) = self.celebration {
+            self.celebration = remaining
+                .checked_sub(delta_time)
+                .filter(|remaining| !remaining.is_zero());
+            if self.celebration.is_none() {
+                self.show_summary();
+            }
+            return Ok(());
+        }
+
+        if self.paused {
+            return Ok(());
+        }
+
+        self.simulate_frame(input, delta_time, width, height)?;
+
+        let snapshot = self.snapshot();
+        if let Some(rewind) = &mut self.rewind {
+            rewind.record(snapshot.clone());
+        }
+        self.recording.record(snapshot, self.rally_hits);
+
+        self.play_sounds(ctx);
+
+        // A match that ended without a celebration goes straight to the summary
+        if self.winner.is_some() && self.celebration.is_none() {
+            self.show_summary();
+        }
+
+        Ok(())
+    }
+
+    /// Saves the frames of the match's longest rally as a highlight.
+    fn export_best_rally(&self) {
+        match self.recording.export_best_rally(BEST_RALLY_EXPORT_PATH) {
+            Ok(Some(hits)) => {
+                log::info!("Exported the best rally ({hits} hits) to {BEST_RALLY_EXPORT_PATH}")
+            }
+            Ok(None) => log::info!("No rally to export, nobody returned the ball"),
+            Err(e) => {
+                log::warn!("Could not export the best rally to {BEST_RALLY_EXPORT_PATH}: {e}")
+            }
+        }
+    }
+
+    /// Saves the finished match's stats and shows its summary.
+    fn show_summary(&mut self) {
+        if let Err(e) = self.stats.export(STATS_EXPORT_PATH) {
+            log::warn!("Could not export match stats to {STATS_EXPORT_PATH}: {e}");
+        }
+        self.summary_selection = 0;
+        self.screen = Screen::Summary;
+    }
+
+    /// Whether the match clock is ticking, which it only does during active play.
+    fn clock_running(&self) -> bool {
+        self.screen == Screen::Playing && !self.paused && self.ready.is_none() && !self.warmup
+    }
+
+    /// Marks players who pressed their serve key during the warmup, with the AI always ready.
+    /// Once both are, the real match starts from a fresh round with scoring switched on.
+    fn handle_warmup(&mut self, input: &InputFrame, width: f32, height: f32) {
+        if !self.warmup {
+            return;
+        }
+        if self.settings.opponent == Opponent::Ai {
+            self.warmup_ready.1 = true;
+        }
+        // Presses only count from the frame after the match started
+        let armed = std::mem::replace(&mut self.warmup_armed, true);
+        for controls in &self.settings.controls {
+            if armed && input.just_pressed.contains(&controls.serve) {
+                *controls.side.of_mut(&mut self.warmup_ready) = true;
+            }
+        }
+        if !(self.warmup_ready.0 && self.warmup_ready.1) {
+            return;
+        }
+
+        log::info!("Warmup over, starting the match");
+        self.warmup = false;
+        self.score = (0, 0);
+        self.stats = MatchStats::default();
+        self.recording.clear();
+        self.match_time = std::time::Duration::ZERO;
+        self.reset_round(width, height);
+        self.delay_duration = self.serve_delay();
+    }
+
+    /// Marks players who pressed their up key as ready. Once both are, the ready check ends
+    /// and the usual countdown to the first serve begins. Returns true while still waiting.
+    fn handle_ready_check(&mut self, input: &InputFrame) -> bool {
+        let Some(mut ready) = self.ready else {
+            return false;
+        };
+        for controls in &self.settings.controls {
+            if input.just_pressed.contains(&controls.up) {
+                *controls.side.of_mut(&mut ready) = true;
+            }
+        }
+
+        if ready.0 && ready.1 {
+            self.ready = None;
+            if self.delay_duration.is_zero() {
+                self.delay_duration = self.serve_delay();
+            }
+            false
+        } else {
+            self.ready = Some(ready);
+            true
+        }
+    }
+
+    /// Runs one frame of active play: timers, serving, paddles and the ball.
+    /// Doesn't need a `Context`, so scripted input can drive it headlessly.
+    pub fn simulate_frame(
+        &mut self,
+        input: &InputFrame,
+        delta_time: std::time::Duration,
+        width: f32,
+        height: f32,
+    ) -> GameResult {
+        // Skip the simulation entirely while the window has no usable area (e.g. minimized)
+        if !Self::has_valid_bounds(width, height) {
+            return Ok(());
+        }
+
+        // Nothing moves until both players are ready
+        if self.handle_ready_check(input) {
+            return Ok(());
+        }
+        self.handle_warmup(input, width, height);
+
+        if let Some((side, remaining)) = self.goal_flash {
+            self.goal_flash = remaining
+                .checked_sub(delta_time)
+                .filter(|remaining| !remaining.is_zero())
+                .map(|remaining| (side, remaining));
+        }
+
+        self.go_flash = self.go_flash.saturating_sub(delta_time);
+        self.round_reset_cooldown = self.round_reset_cooldown.saturating_sub(delta_time);
+        if input.just_pressed.contains(&KeyCode::R) {
+            self.redo_round(width, height);
+        }
+        if let Some((result, remaining)) = self.coin_flip {
+            self.coin_flip = remaining
+                .checked_sub(delta_time)
+                .filter(|remaining| !remaining.is_zero())
+                .map(|remaining| (result, remaining));
+        }
+        self.serve_grace = self.serve_grace.saturating_sub(delta_time);
+
+        // Only active play counts towards the match clock, never a pause or another screen
+        if self.clock_running() {
+            self.match_time += delta_time;
+        }
+
+        // Time attack ends on the clock, unless the score is level
+        self.check_for_winner();
+
+        if self.settings.practice && self.delay_duration > std::time::Duration::ZERO {
+            self.handle_practice_serve(input);
+        }
+
+        if self.delay_duration > std::time::Duration::ZERO {
+            if self.settings.move_during_delay {
+                self.handle_paddle_movement(input, height)?;
+            }
+
+            // An endless delay lasts until the server presses their serve key
+            self.delay_duration = if self.delay_duration == std::time::Duration::MAX {
+                if self.serve_key_pressed(input) {
+                    std::time::Duration::ZERO
+                } else {
+                    std::time::Duration::MAX
+                }
+            } else {
+                self.delay_duration
+                    .checked_sub(delta_time)
+                    .unwrap_or(std::time::Duration::ZERO)
+            };
+            if self.delay_duration.is_zero() {
+                self.go_flash = GO_FLASH_DURATION;
+                self.serve_grace = SERVE_GRACE_DURATION;
+            }
+            return Ok(());
+        }
+
+        self.handle_paddle_movement(input, height)?;
+
+        // The deciding rally plays out a little slower, back to normal once it's no longer one
+        self.time_scale = if self.settings.match_point_slow_mo && self.is_match_point() {
+            MATCH_POINT_TIME_SCALE
+        } else {
+            1.
+        };
+
+        // A ball nobody has touched for too long ends the rally without a point
+        self.since_paddle_hit += delta_time;
+        if let Some(timeout) = self.settings.dead_ball_timeout
+            && self.since_paddle_hit >= timeout
+        {
+            log::info!("No paddle hit for {timeout:?}, re-serving");
+            self.serve_from_center(width, height);
+            self.delay_duration = self.serve_delay();
+            return Ok(());
+        }
+
+        if let Some(interval) = self.settings.power_up_interval {
+            self.power_up_spawner.interval = interval;
+            if let Some(kind) = self.power_up_spawner.tick(delta_time, &mut self.rng)
+                && self.power_ups.len() < MAX_POWER_UPS
+            {
+                let power_up = powerups::place_power_up(kind, width, height, &mut self.rng);
+                self.power_ups.push(power_up);
+            }
+        }
+
+        self.step(width, height)
+    }
+
+    /// The paddles, obstacles, power-ups and balls on the field, in the order they're drawn.
+    pub fn drawables(&self) -> impl Iterator<Item = Drawable> {
+        let foreground = self.theme.foreground;
+        let paddles = [&self.left_paddle, &self.right_paddle]
+            .into_iter()
+            .map(move |paddle| Drawable::Paddle {
+                rect: paddle.rect(),
+                color: foreground,
+            });
+        let obstacles =
+            self.obstacles
+                .iter()
+                .chain(&self.net)
+                .map(move |&rect| Drawable::Obstacle {
+                    rect,
+                    color: foreground,
+                });
+        let power_ups = self.power_ups.iter().map(|power_up| Drawable::PowerUp {
+            rect: power_up.rect,
+            color: power_up.kind.color(),
+        });
+
+        let radius = self.ball_radius();
+        let balls = std::iter::once(&self.ball)
+            .chain(&self.extra_balls)
+            .map(move |ball| {
+                let stretch = if self.config.display.ball_stretch {
+                    Ball::stretch_factor(ball.speed())
+                } else {
+                    1.
+                };
+                Drawable::Ball {
+                    pos: ball.pos,
+                    radius,
+                    rotation: ball.vel.y.atan2(ball.vel.x),
+                    stretch,
+                    color: if ball.is_mercy() {
+                        theme::MERCY_BALL_COLOR
+                    } else {
+                        self.theme.ball_color(ball.last_hitter)
+                    },
+                }
+            });
+
+        paddles.chain(obstacles).chain(power_ups).chain(balls)
+    }
+
+    /// Draws everything `drawables` lists, each on top of its shadow.
+    fn draw_drawables(&self, canvas: &mut graphics::Canvas, ctx: &mut Context) -> GameResult {
+        for drawable in self.drawables() {
+            match drawable {
+                Drawable::Paddle { rect, color }
+                | Drawable::Obstacle { rect, color }
+                | Drawable::PowerUp { rect, color } => {
+                    let mesh = graphics::Mesh::new_rectangle(
+                        ctx,
+                        graphics::DrawMode::fill(),
+                        rect,
+                        Color::WHITE,
+                    )?;
+                    draw_with_shadow_at(
+                        canvas,
+                        &mesh,
+                        &self.theme,
+                        Point2 { x: 0., y: 0. },
+                        graphics::DrawParam::new().color(color),
+                    );
+                }
+                Drawable::Ball {
+                    pos,
+                    radius,
+                    rotation,
+                    stretch,
+                    color,
+                } => {
+                    // Built around the origin so it can be turned and stretched along its velocity
+                    let mesh = graphics::Mesh::new_circle(
+                        ctx,
+                        graphics::DrawMode::fill(),
+                        Point2 { x: 0., y: 0. },
+                        radius,
+                        self.config.display.ball_tolerance(),
+                        Color::WHITE,
+                    )?;
+                    let shape = graphics::DrawParam::new()
+                        .color(color)
+                        .rotation(rotation)
+                        .scale(Point2 {
+                            x: stretch,
+                            y: 1. / stretch,
+                        });
+                    draw_with_shadow_at(canvas, &mesh, &self.theme, pos, shape);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Draws a coordinate grid with its lines labelled along the top and left edges, and
+    /// marks the paddles' and balls' centers at the nearest grid point with their exact position.
+    fn draw_debug_grid(
+        &self,
+        canvas: &mut graphics::Canvas,
+        ctx: &mut Context,
+        width: f32,
+        height: f32,
+    ) -> GameResult {
+        let line_color = Color::new(0.3, 0.8, 1.0, 0.25);
+        let mut builder = graphics::MeshBuilder::new();
+        for x in geometry::grid_lines(DEBUG_GRID_SPACING, width) {
+            builder.line(&[[x, 0.], [x, height]], 1.0, line_color)?;
+        }
+        for y in geometry::grid_lines(DEBUG_GRID_SPACING, height) {
+            builder.line(&[[0., y], [width, y]], 1.0, line_color)?;
+        }
+
+        let centers = [
+            Point2 {
+                x: self.left_paddle.pos.x + self.left_paddle.width / 2.,
+                y: self.left_paddle.pos.y + self.left_paddle.height / 2.,
+            },
+            Point2 {
+                x: self.right_paddle.pos.x + self.right_paddle.width / 2.,
+                y: self.right_paddle.pos.y + self.right_paddle.height / 2.,
+            },
+        ]
+        .into_iter()
+        .chain(
+            std::iter::once(&self.ball)
+                .chain(&self.extra_balls)
+                .map(|ball| ball.pos),
+        );
+        let mut labels = Vec::new();
+        for center in centers {
+            let snapped = Point2 {
+                x: geometry::snap_to_grid(center.x, DEBUG_GRID_SPACING),
+                y: geometry::snap_to_grid(center.y, DEBUG_GRID_SPACING),
+            };
+            let [x, y] = [snapped.x, snapped.y];
+            builder.line(
+                &[[x - DEBUG_MARKER_SIZE, y], [x + DEBUG_MARKER_SIZE, y]],
+                2.0,
+                Color::YELLOW,
+            )?;
+            builder.line(
+                &[[x, y - DEBUG_MARKER_SIZE], [x, y + DEBUG_MARKER_SIZE]],
+                2.0,
+                Color::YELLOW,
+            )?;
+            labels.push((
+                format!("{:.0},{:.0}", center.x, center.y),
+                Point2 {
+                    x: x + DEBUG_MARKER_SIZE,
+                    y: y + DEBUG_MARKER_SIZE,
+                },
+                Color::YELLOW,
+            ));
+        }
+        let grid = graphics::Mesh::from_data(ctx, builder.build());
+        canvas.draw(&grid, graphics::DrawParam::new());
+
+        for x in geometry::grid_lines(DEBUG_GRID_SPACING, width) {
+            labels.push((format!("{x:.0}"), Point2 { x: x + 2., y: 2. }, line_color));
+        }
+        for y in geometry::grid_lines(DEBUG_GRID_SPACING, height).skip(1) {
+            labels.push((format!("{y:.0}"), Point2 { x: 2., y: y + 2. }, line_color));
+        }
+        for (label, dest, color) in labels {
+            let mut text = graphics::Text::new(label);
+            text.set_scale(DEBUG_GRID_LABEL_SCALE);
+            canvas.draw(
+                &text,
+                graphics::DrawParam::new()
+                    .dest(dest)
+                    .color(Color { a: 1.0, ..color }),
+            );
+        }
+        Ok(())
+    }
+
+    /// Draws the paddles, ball and in-play overlays.
+    fn draw_field(&mut self, canvas: &mut graphics::Canvas, ctx: &mut Context) -> GameResult {
+        let (width, height) = ctx.gfx.drawable_size();
+        // The field itself is drawn mirrored and shaken about by the celebration, the HUD on top
+        // of it isn't
+        let mut view = geometry::screen_coordinates(width, height, self.settings.mirror_view);
+        if let Some(remaining) = self.celebration {
+            view = celebration_view(view, remaining);
+        }
+        canvas.set_screen_coordinates(view);
+
+        if let Some(zone) = self.burst_zone {
+            let zone = graphics::Mesh::new_rectangle(
+                ctx,
+                graphics::DrawMode::fill(),
+                zone,
+                Color::new(1.0, 0.6, 0.1, 0.08),
+            )?;
+            canvas.draw(&zone, graphics::DrawParam::new());
+        }
+
+        if self.debug_grid == Some(true) {
+            self.draw_debug_grid(canvas, ctx, width, height)?;
+        }
+
+        // Tint the wall a paddle is held against, so players know it can't go any further
+        for (paddle, wall) in [
+            (&self.left_paddle, self.pressed_walls.0),
+            (&self.right_paddle, self.pressed_walls.1),
+        ] {
+            let Some(wall) = wall else {
+                continue;
+            };
+            let y = match wall {
+                Wall::Top => 0.,
+                Wall::Bottom => height - WALL_TINT_HEIGHT,
+            };
+            let tint = graphics::Mesh::new_rectangle(
+                ctx,
+                graphics::DrawMode::fill(),
+                Rect::new(
+                    paddle.pos.x - paddle.width,
+                    y,
+                    paddle.width * 3.,
+                    WALL_TINT_HEIGHT,
+                ),
+                Color::new(1.0, 1.0, 1.0, 0.35),
+            )?;
+            canvas.draw(&tint, graphics::DrawParam::new());
+        }
+
+        // A bar under each recharging paddle fills up until it can dash again
+        for paddle in [&self.left_paddle, &self.right_paddle] {
+            if paddle.dash_cooldown == 0 {
+                continue;
+            }
+            let charged = 1. - paddle.dash_cooldown as f32 / DASH_COOLDOWN_FRAMES as f32;
+            let bar = graphics::Mesh::new_rectangle(
+                ctx,
+                graphics::DrawMode::fill(),
+                Rect::new(
+                    paddle.pos.x,
+                    paddle.pos.y + paddle.height + DASH_BAR_GAP,
+                    paddle.width * charged,
+                    DASH_BAR_HEIGHT,
+                ),
+                Color::new(0.3, 0.7, 1.0, 0.8),
+            )?;
+            canvas.draw(&bar, graphics::DrawParam::new());
+        }
+
+        if self.config.display.speed_lines {
+            let mut builder = graphics::MeshBuilder::new();
+            let mut any_lines = false;
+            for ball in std::iter::once(&self.ball).chain(&self.extra_balls) {
+                for [start, end] in ball.speed_lines(self.ball_radius()) {
+                    // Each line fades out towards its tail, one piece at a time
+                    let point_at = |t: f32| Point2 {
+                        x: start.x + (end.x - start.x) * t,
+                        y: start.y + (end.y - start.y) * t,
+                    };
+                    for piece in 0..SPEED_LINE_PIECES {
+                        let t = piece as f32 / SPEED_LINE_PIECES as f32;
+                        let next = (piece + 1) as f32 / SPEED_LINE_PIECES as f32;
+                        builder.line(
+                            &[point_at(t), point_at(next)],
+                            SPEED_LINE_WIDTH,
+                            Color::new(1.0, 1.0, 1.0, 0.45 * (1. - t)),
+                        )?;
+                    }
+                    any_lines = true;
+                }
+            }
+            if any_lines {
+                let lines = graphics::Mesh::from_data(ctx, builder.build());
+                canvas.draw(&lines, graphics::DrawParam::new());
+            }
+        }
+
+        self.draw_drawables(canvas, ctx)?;
+
+        if self.settings.practice && self.delay_duration.is_zero() {
+            let dashes = dashed_segments(&self.predicted_path, PREDICTION_DASH, PREDICTION_GAP);
+            if !dashes.is_empty() {
+                let mut builder = graphics::MeshBuilder::new();
+                for dash in &dashes {
+                    builder.line(dash, 2.0, Color::new(1.0, 1.0, 1.0, 0.4))?;
+                }
+                let path = graphics::Mesh::from_data(ctx, builder.build());
+                canvas.draw(&path, graphics::DrawParam::new());
+            }
+        }
+
+        if self.settings.training_targets {
+            for target in &self.training.targets {
+                let zone = graphics::Mesh::new_rectangle(
+                    ctx,
+                    graphics::DrawMode::fill(),
+                    *target,
+                    Color::new(0.0, 1.0, 0.4, 0.5),
+                )?;
+                canvas.draw(&zone, graphics::DrawParam::new());
+            }
+        }
+
+        // Flash the wall that was just scored on, fading out over time
+        if let Some((side, remaining)) = self.goal_flash {
+            let x = match side {
+                Side::Left => 0.,
+                Side::Right => width - GOAL_FLASH_WIDTH,
+            };
+            let alpha = remaining.as_secs_f32() / GOAL_FLASH_DURATION.as_secs_f32();
+            let flash = graphics::Mesh::new_rectangle(
+                ctx,
+                graphics::DrawMode::fill(),
+                Rect::new(x, 0., GOAL_FLASH_WIDTH, height),
+                Color::new(1.0, 0.8, 0.0, alpha),
+            )?;
+            canvas.draw(&flash, graphics::DrawParam::new());
+        }
+
+        canvas.set_screen_coordinates(geometry::screen_coordinates(width, height, false));
+
+        // The celebration flashes the screen gold a few times, fading out
+        if let Some(remaining) = self.celebration {
+            let fade = remaining.as_secs_f32() / CELEBRATION_DURATION.as_secs_f32();
+            let pulse = (fade * CELEBRATION_FLASHES * std::f32::consts::PI)
+                .sin()
+                .abs();
+            let flash = graphics::Mesh::new_rectangle(
+                ctx,
+                graphics::DrawMode::fill(),
+                Rect::new(0., 0., width, height),
+                Color::new(1.0, 0.8, 0.0, 0.3 * fade * pulse),
+            )?;
+            canvas.draw(&flash, graphics::DrawParam::new());
+        }
+
+        // Draw score, unless the spectator HUD shows it instead
+        if self.config.display.spectator_hud {
+            self.draw_spectator_hud(canvas, ctx)?;
+        } else if !self.settings.zen {
+            self.draw_score(canvas, ctx)?;
+        }
+
+        if self.settings.training_targets {
+            let accuracy = match self.training.accuracy() {
+                Some(accuracy) => format!(
+                    "Accuracy: {}/{} ({accuracy:.0}%)",
+                    self.training.hits, self.training.attempts
+                ),
+                None => "Accuracy: -".to_string(),
+            };
+            let mut accuracy_text = graphics::Text::new(accuracy);
+            accuracy_text.set_scale(16.0);
+            let text_measure = accuracy_text.measure(ctx)?;
+            canvas.draw(
+                &accuracy_text,
+                graphics::DrawParam::new()
+                    .dest(Point2 {
+                        x: width - text_measure.x - 20.0,
+                        y: height - text_measure.y - 10.0,
+                    })
+                    .color(self.theme.text),
+            );
+        }
+
+        // Heat meter under the score, glowing once a double point is on the line
+        if self.settings.heat_meter && !self.settings.zen {
+            let frame = Rect::new(
+                (width - HEAT_BAR_WIDTH) / 2.,
+                HEAT_BAR_Y,
+                HEAT_BAR_WIDTH,
+                HEAT_BAR_HEIGHT,
+            );
+            let outline = graphics::Mesh::new_rectangle(
+                ctx,
+                graphics::DrawMode::stroke(1.0),
+                frame,
+                self.theme.foreground,
+            )?;
+            canvas.draw(&outline, graphics::DrawParam::new());
+            if self.heat.heat > 0. {
+                let color = if self.heat.charged {
+                    Color::new(1.0, 0.3, 0.1, 1.0)
+                } else {
+                    Color::new(1.0, 0.7, 0.2, 0.8)
+                };
+                let fill = graphics::Mesh::new_rectangle(
+                    ctx,
+                    graphics::DrawMode::fill(),
+                    Rect::new(frame.x, frame.y, frame.w * self.heat.heat, frame.h),
+                    color,
+                )?;
+                canvas.draw(&fill, graphics::DrawParam::new());
+            }
+        }
+
+        // Show how full the field is in multi-ball mode
+        if self.settings.multi_ball {
+            let mut count_text = graphics::Text::new(format!(
+                "Balls: {}/{}",
+                self.balls_in_play(),
+                self.settings.max_balls
+            ));
+            count_text.set_scale(16.0);
+            let text_measure = count_text.measure(ctx)?;
+            canvas.draw(
+                &count_text,
+                graphics::DrawParam::new()
+                    .dest(Point2 {
+                        x: (width / 2.0) - (text_measure.x / 2.0),
+                        y: 50.0,
+                    })
+                    .color(self.theme.text),
+            );
+        }
+
+        // Draw remaining lives as a row of dots under each half of the score
+        if self.settings.elimination {
+            for (side, center_x) in [(Side::Left, width / 4.), (Side::Right, width * 3. / 4.)] {
+                let lives = *side.of(&self.lives);
+                let row_width = f32::from(lives.saturating_sub(1)) * LIFE_ICON_SPACING;
+                for i in 0..lives {
+                    let icon = graphics::Mesh::new_circle(
+                        ctx,
+                        graphics::DrawMode::fill(),
+                        Point2 {
+                            x: center_x - (row_width / 2.) + f32::from(i) * LIFE_ICON_SPACING,
+                            y: 32.0,
+                        },
+                        LIFE_ICON_RADIUS,
+                        1.0,
+                        Color::RED,
+                    )?;
+                    canvas.draw(&icon, graphics::DrawParam::new());
+                }
+            }
+        }
+
+        if let Some(ready) = self.ready {
+            let status = |side: Side| {
+                let key = self
+                    .settings
+                    .controls
+                    .iter()
+                    .find(|controls| controls.side == side)
+                    .map(|controls| format!("{:?}", controls.up))
+                    .unwrap_or_default();
+                if *side.of(&ready) {
+                    format!("{} ready", side.name())
+                } else {
+                    format!("{}: press {key}", side.name())
+                }
+            };
+            draw_centered_text(
+                canvas,
+                self.text(format!(
+                    "{}   /   {}",
+                    status(Side::Left),
+                    status(Side::Right)
+                )),
+                24.0,
+                Point2 {
+                    x: width / 2.,
+                    y: height * 2. / 3.,
+                },
+                self.theme.text,
+            );
+        }
+
+        if self.warmup {
+            let status = |side: Side| {
+                let key = self
+                    .settings
+                    .controls
+                    .iter()
+                    .find(|controls| controls.side == side)
+                    .map(|controls| format!("{:?}", controls.serve))
+                    .unwrap_or_default();
+                if *side.of(&self.warmup_ready) {
+                    format!("{} ready", side.name())
+                } else {
+                    format!("{}: press {key}", side.name())
+                }
+            };
+            draw_centered_text(
+                canvas,
+                self.text(format!(
+                    "Warmup   {}   /   {}",
+                    status(Side::Left),
+                    status(Side::Right)
+                )),
+                24.0,
+                Point2 {
+                    x: width / 2.,
+                    y: height * 2. / 3.,
+                },
+                self.theme.text,
+            );
+        }
+
+        // Spin through the sides, then settle on the flip's result
+        if let Some((result, remaining)) = self.coin_flip {
+            let side = if remaining > COIN_FLIP_SETTLE {
+                let elapsed = COIN_FLIP_DURATION.saturating_sub(remaining).as_millis();
+                if (elapsed / COIN_FLIP_SPIN_INTERVAL_MS).is_multiple_of(2) {
+                    Side::Left
+                } else {
+                    Side::Right
+                }
+            } else {
+                result
+            };
+            let message = match side {
+                Side::Left => "Coin flip: Left",
+                Side::Right => "Coin flip: Right",
+            };
+            draw_centered_text(
+                canvas,
+                self.text(message),
+                30.0,
+                Point2 {
+                    x: width / 2.,
+                    y: height / 3.,
+                },
+                self.theme.text,
+            );
+        }
+
+        // Let the players know who's serving during the countdown
+        if self.winner.is_none() && self.coin_flip.is_none() && !self.delay_duration.is_zero() {
+            let mut message = match self.server {
+                Side::Left => "Left player to serve".to_string(),
+                Side::Right => "Right player to serve".to_string(),
+            };
+            if self.delay_duration == std::time::Duration::MAX
+                && let Some(controls) = self
+                    .settings
+                    .controls
+                    .iter()
+                    .find(|controls| controls.side == self.server)
+            {
+                message.push_str(&format!(" (press {:?})", controls.serve));
+            }
+            let mut server_text = graphics::Text::new(message);
+            server_text.set_scale(20.0);
+            let text_measure = server_text.measure(ctx)?;
+            canvas.draw(
+                &server_text,
+                graphics::DrawParam::new()
+                    .dest(Point2 {
+                        x: (width / 2.0) - (text_measure.x / 2.0),
+                        y: (height / 2.0) + 40.0,
+                    })
+                    .color(Color::WHITE),
+            );
+        }
+
+        if !self.go_flash.is_zero() {
+            let alpha = self.go_flash.as_secs_f32() / GO_FLASH_DURATION.as_secs_f32();
+            let mut go_text = graphics::Text::new("GO!");
+            go_text.set_scale(40.0);
+            let text_measure = go_text.measure(ctx)?;
+            canvas.draw(
+                &go_text,
+                graphics::DrawParam::new()
+                    .dest(Point2 {
+                        x: (width / 2.0) - (text_measure.x / 2.0),
+                        y: (height / 2.0) - (text_measure.y / 2.0),
+                    })
+                    .color(Color::new(0.0, 1.0, 0.0, alpha)),
+            );
+        }
+
+        if self.paused {
+            let message = if self.controller_lost {
+                "Reconnect controller"
+            } else {
+                match self.settings.opponent {
+                    Opponent::Human => "Paused\n\nA: play against the AI",
+                    Opponent::Ai => "Paused\n\nA: play against a human",
+                }
+            };
+            let mut pause_text = self.text(message);
+            if let Some(rewind) = &self.rewind {
+                pause_text.add(format!(
+                    "\n\nRewind (,/.): {} frames back",
+                    rewind.frames_back()
+                ));
+            }
+            pause_text.set_scale(30.0);
+            let text_measure = pause_text.measure(ctx)?;
+            canvas.draw(
+                &pause_text,
+                graphics::DrawParam::new()
+                    .dest(Point2 {
+                        x: (width / 2.0) - (text_measure.x / 2.0),
+                        y: (height / 2.0) - (text_measure.y / 2.0),
+                    })
+                    .color(Color::RED),
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Draws the score as laid out by the theme, with the time left in a timed match.
+    fn draw_score(&self, canvas: &mut graphics::Canvas, ctx: &mut Context) -> GameResult {
+        let layout = self.theme.score_layout;
+        let width = ctx.gfx.drawable_size().0;
+        let scaled = |content: String| {
+            let mut text = self.text(content);
+            text.set_scale(layout.scale);
+            text
+        };
+
+        // Split scores leave only the clock in the middle
+        let mut center_text = if layout.split {
+            let left = scaled(self.score.0.to_string());
+            let right = scaled(self.score.1.to_string());
+            let (left_pos, right_pos) =
+                layout.split_positions(width, left.measure(ctx)?.x, right.measure(ctx)?.x);
+            let param = graphics::DrawParam::new().color(layout.color);
+            canvas.draw(&left, param.dest(left_pos));
+            canvas.draw(&right, param.dest(right_pos));
+            scaled(String::new())
+        } else {
+            scaled(format!("{} - {}", self.score.0, self.score.1))
+        };
+        if let Some(limit) = self.settings.time_limit {
+            let remaining = limit.saturating_sub(self.match_time).as_secs();
+            let separator = if layout.split { "" } else { "\n" };
+            center_text.add(format!(
+                "{separator}{}:{:02}",
+                remaining / 60,
+                remaining % 60
+            ));
+        }
+
+        let text_measure = center_text.measure(ctx)?;
+        canvas.draw(
+            &center_text,
+            graphics::DrawParam::new()
+                .dest(Point2 {
+                    x: (width / 2.0) - (text_measure.x / 2.0),
+                    y: layout.y_offset,
+                })
+                .color(layout.color),
+        );
+        Ok(())
+    }
+
+    /// Draws the spectator HUD panel in the top-left corner.
+    fn draw_spectator_hud(&self, canvas: &mut graphics::Canvas, ctx: &mut Context) -> GameResult {
+        let (score, details) =
+            hud::spectator_lines(self.score, self.server, self.rally_hits, self.ball.speed());
+        let mut text =
+            self.text(graphics::TextFragment::new(score).scale(hud::SPECTATOR_SCORE_SCALE));
+        text.set_scale(hud::SPECTATOR_DETAIL_SCALE);
+        for line in details {
+            text.add(format!("\n{line}"));
+        }
+
+        let (x, y) = hud::SPECTATOR_HUD_ORIGIN;
+        let measure = text.measure(ctx)?;
+        let panel = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            Rect::new(
+                x,
+                y,
+                measure.x + 2. * hud::SPECTATOR_HUD_PADDING,
+                measure.y + 2. * hud::SPECTATOR_HUD_PADDING,
+            ),
+            Color::new(0.0, 0.0, 0.0, 0.6),
+        )?;
+        canvas.draw(&panel, graphics::DrawParam::new());
+        canvas.draw(
+            &text,
+            graphics::DrawParam::new()
+                .dest(Point2 {
+                    x: x + hud::SPECTATOR_HUD_PADDING,
+                    y: y + hud::SPECTATOR_HUD_PADDING,
+                })
+                .color(Color::WHITE),
+        );
+        Ok(())
+    }
+
+    /// Draws the console's recent output and input line along the bottom of the window.
+    fn draw_console(&self, canvas: &mut graphics::Canvas, ctx: &mut Context) -> GameResult {
+        let mut text = self.text("");
+        text.set_scale(console::CONSOLE_TEXT_SCALE);
+        for line in &self.console.history {
+            text.add(format!("{line}\n"));
+        }
+        text.add(format!("> {}_", self.console.input));
+
+        let (width, height) = ctx.gfx.drawable_size();
+        let panel_height = text.measure(ctx)?.y + 2. * console::CONSOLE_PADDING;
+        let panel = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            Rect::new(0., height - panel_height, width, panel_height),
+            Color::new(0.0, 0.0, 0.0, 0.75),
+        )?;
+        canvas.draw(&panel, graphics::DrawParam::new());
+        canvas.draw(
+            &text,
+            graphics::DrawParam::new()
+                .dest(Point2 {
+                    x: console::CONSOLE_PADDING,
+                    y: height - panel_height + console::CONSOLE_PADDING,
+                })
+                .color(Color::WHITE),
+        );
+        Ok(())
+    }
+
+    /// Text in the custom font, or the default one if none was loaded.
+    fn text(&self, content: impl Into<graphics::TextFragment>) -> graphics::Text {
+        let mut text = graphics::Text::new(content);
+        if let Some(font) = self.font {
+            text.set_font(font);
+        }
+        text
+    }
+
+    fn draw_menu(&self, canvas: &mut graphics::Canvas, center: Point2<f32>) {
+        let first_server = match self.first_server {
+            Some(side) => side.name(),
+            None => "Random",
+        };
+        let modes: Vec<String> = GameMode::ALL
+            .iter()
+            .enumerate()
+            .map(|(i, mode)| {
+                if i == self.menu_selection {
+                    format!("> {} <", mode.name())
+                } else {
+                    mode.name().to_string()
+                }
+            })
+            .collect();
+        let (left_wins, right_wins) = self.records.wins(GameMode::ALL[self.menu_selection].name());
+        draw_centered_text(
+            canvas,
+            self.text(format!(
+                "Rusty Pong\n\n{}\n\nWins: Left {left_wins} - {right_wins} Right\n\nFirst serve: {first_server} (Left/Right to change)\nBoard: {} (B to change)\n\nPress Enter to play",
+                modes.join("\n"),
+                self.settings.board.name()
+            )),
+            24.0,
+            center,
+            self.theme.text,
+        );
+    }
+
+    /// Shows a window of the settings rows around the selection, with markers for any
+    /// scrolled out of view above or below.
+    fn draw_settings(&self, canvas: &mut graphics::Canvas, center: Point2<f32>) {
+        let window = scroll_window(
+            self.settings_selection,
+            SettingsItem::ALL.len(),
+            SETTINGS_ROWS_SHOWN,
+        );
+        let more = |hidden: bool| if hidden { "..." } else { "" };
+        let (above, below) = (
+            more(window.start > 0),
+            more(window.end < SettingsItem::ALL.len()),
+        );
+        let rows: Vec<String> = SettingsItem::ALL
+            .iter()
+            .enumerate()
+            .skip(window.start)
+            .take(window.len())
+            .map(|(i, item)| {
+                let label = self.settings_item_label(*item);
+                if i == self.settings_selection {
+                    format!("< {label} >")
+                } else {
+                    label
+                }
+            })
+            .collect();
+        draw_centered_text(
+            canvas,
+            self.text(format!(
+                "Settings\n{above}\n{}\n{below}\nC for custom colors, Tab to close",
+                rows.join("\n")
+            )),
+            24.0,
+            center,
+            Color::WHITE,
+        );
+    }
+
+    /// Lists each element's color, with the paddles, ball and a line of text as a live preview.
+    fn draw_color_picker(
+        &self,
+        canvas: &mut graphics::Canvas,
+        ctx: &mut Context,
+        center: Point2<f32>,
+    ) -> GameResult {
+        let custom_theme = self.config.custom_theme.unwrap_or_default();
+        let rows: Vec<String> = ThemeElement::ALL
+            .iter()
+            .enumerate()
+            .map(|(i, element)| {
+                let [r, g, b] = custom_theme.rgb(*element);
+                let label = format!("{}: {r}, {g}, {b}", element.name());
+                if i == self.color_picker_selection {
+                    format!("> {label} <")
+                } else {
+                    label
+                }
+            })
+            .collect();
+        draw_centered_text(
+            canvas,
+            self.text(format!(
+                "Custom colors\n\n{}\n\nHold R/G/B to raise a channel, with Shift to lower it\nDelete for the default colors, Tab to go back",
+                rows.join("\n")
+            )),
+            24.0,
+            Point2 {
+                x: center.x,
+                y: center.y - 60.,
+            },
+            self.theme.text,
+        );
+
+        let preview_y = center.y + 130.;
+        let paddle = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            Rect::new(0., 0., PADDLE_WIDTH, 60.),
+            self.theme.foreground,
+        )?;
+        for x in [center.x - 120., center.x + 120. - PADDLE_WIDTH] {
+            canvas.draw(
+                &paddle,
+                graphics::DrawParam::new().dest(Point2 {
+                    x,
+                    y: preview_y - 30.,
+                }),
+            );
+        }
+        let ball = graphics::Mesh::new_circle(
+            ctx,
+            graphics::DrawMode::fill(),
+            Point2 {
+                x: center.x,
+                y: preview_y,
+            },
+            BALL_RADIUS,
+            1.0,
+            self.theme.ball,
+        )?;
+        canvas.draw(&ball, graphics::DrawParam::new());
+        Ok(())
+    }
+
+    fn draw_summary(
+        &self,
+        canvas: &mut graphics::Canvas,
+        ctx: &mut Context,
+        center: Point2<f32>,
+    ) -> GameResult {
+        // The scrubbed-to frame of the match plays out behind the results
+        if !self.recording.is_empty() {
+            self.draw_drawables(canvas, ctx)?;
+
+            let (width, height) = ctx.gfx.drawable_size();
+            let track = Rect::new(
+                SCRUB_BAR_MARGIN,
+                height - SCRUB_BAR_MARGIN - SCRUB_BAR_HEIGHT,
+                width - 2. * SCRUB_BAR_MARGIN,
+                SCRUB_BAR_HEIGHT,
+            );
+            let filled = Rect {
+                w: track.w * self.recording.progress(),
+                ..track
+            };
+            for (rect, alpha) in [(track, 0.25), (filled, 0.8)] {
+                let bar = graphics::Mesh::new_rectangle(
+                    ctx,
+                    graphics::DrawMode::fill(),
+                    rect,
+                    Color::new(1.0, 1.0, 1.0, alpha),
+                )?;
+                canvas.draw(&bar, graphics::DrawParam::new());
+            }
+        }
+
+        let winner = match self.winner {
+            Some(Side::Left) => "Left player wins!",
+            Some(Side::Right) => "Right player wins!",
+            None => "Match over",
+        };
+        let duration = self.match_time.as_secs();
+        let options: Vec<String> = SummaryOption::ALL
+            .iter()
+            .enumerate()
+            .map(|(i, option)| {
+                if i == self.summary_selection {
+                    format!("> {} <", option.label())
+                } else {
+                    option.label().to_string()
+                }
+            })
+            .collect();
+
+        draw_centered_text(
+            canvas,
+            self.text(format!(
+                "{winner}\n\nFinal score: {} - {}\nMatch duration: {}:{:02}\nLongest rally: {}\n\n{}\n\nLeft/Right: scrub through the match",
+                self.stats.final_score.0,
+                self.stats.final_score.1,
+                duration / 60,
+                duration % 60,
+                self.stats.longest_rally,
+                options.join("\n"),
+            )),
+            24.0,
+            center,
+            self.theme.text,
+        );
+        Ok(())
+    }
+}
+
+impl EventHandler for GameState {
+    fn update(&mut self, ctx: &mut Context) -> GameResult {
+        self.wait_for_frame_cap();
+
+        let input = InputFrame::capture(&ctx.keyboard, &ctx.gamepad);
+        self.update_frame(ctx, &input)?;
+
+        if self.frame_logger.is_some() {
+            let snapshot = self.snapshot();
+            if let Some(logger) = &mut self.frame_logger
+                && let Err(e) = logger.log(&input, &snapshot)
+            {
+                log::warn!("Frame logging failed, disabling it: {e}");
+                self.frame_logger = None;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> GameResult {
+        // Long rallies slowly warm up the background
+        let background_theme = if self.screen == Screen::Playing {
+            self.theme.with_rally_heat(self.rally_hits)
+        } else {
+            self.theme
+        };
+        let mut canvas = graphics::Canvas::from_frame(ctx, background_theme.background);
+        self.background.draw(&mut canvas, ctx, &background_theme)?;
+
+        let (width, height) = ctx.gfx.drawable_size();
+        let center = Point2 {
+            x: width / 2.,
+            y: height / 2.,
+        };
+        match self.screen {
+            Screen::Playing => self.draw_field(&mut canvas, ctx)?,
+            Screen::Settings => self.draw_settings(&mut canvas, center),
+            Screen::ColorPicker => self.draw_color_picker(&mut canvas, ctx, center)?,
+            Screen::Summary => self.draw_summary(&mut canvas, ctx, center)?,
+            Screen::Menu => self.draw_menu(&mut canvas, center),
+        }
+        if self.console.open {
+            self.draw_console(&mut canvas, ctx)?;
+        }
+
+        canvas.finish(ctx)?;
+
+        if self.screenshot_requested {
+            self.screenshot_requested = false;
+            self.take_screenshot(ctx);
+        }
+
+        Ok(())
+    }
+
+    fn text_input_event(&mut self, _ctx: &mut Context, character: char) -> GameResult {
+        // The backtick that toggles the console shouldn't end up in it
+        if self.console.open && character != '`' && !character.is_control() {
+            self.console.input.push(character);
+        }
+        Ok(())
+    }
+
+    fn resize_event(&mut self, _ctx: &mut Context, width: f32, height: f32) -> GameResult {
+        self.handle_minimize(width, height);
+        self.fit_to_bounds(width, height);
+        Ok(())
+    }
+}
+
+/// The rows of a `len` row list to show, at most `shown` of them, keeping `selection` as
+/// near the middle as the ends of the list allow.
+fn scroll_window(selection: usize, len: usize, shown: usize) -> std::ops::Range<usize> {
+    let start = selection
+        .saturating_sub(shown / 2)
+        .min(len.saturating_sub(shown));
+    start..(start + shown).min(len)
+}
+
+/// `view` zoomed in and shaken for a celebration with `remaining` time left.
+/// The zoom swells and settles back while the shake dies down.
+fn celebration_view(view: Rect, remaining: std::time::Duration) -> Rect {
+    let left = remaining.as_secs_f32() / CELEBRATION_DURATION.as_secs_f32();
+    let progress = 1. - left.clamp(0., 1.);
+    let zoom = 1. + CELEBRATION_ZOOM * (progress * std::f32::consts::PI).sin();
+    let shake = CELEBRATION_SHAKE * left * (progress * CELEBRATION_SHAKE_RATE).sin();
+
+    let (w, h) = (view.w / zoom, view.h / zoom);
+    Rect::new(
+        view.x + (view.w - w) / 2. + shake,
+        view.y + (view.h - h) / 2. + shake / 2.,
+        w,
+        h,
+    )
+}
+
+/// Splits a polyline into dashes of length `dash` separated by `gap`.
+fn dashed_segments(path: &[Point2<f32>], dash: f32, gap: f32) -> Vec<[Point2<f32>; 2]> {
+    let mut dashes = Vec::new();
+    // Distance into the current dash-gap cycle, carried over between segments
+    let mut phase = 0.;
+    for segment in path.windows(2) {
+        let (start, end) = (segment[0], segment[1]);
+        let (dx, dy) = (end.x - start.x, end.y - start.y);
+        let length = (dx * dx + dy * dy).sqrt();
+        if length <= 0. {
+            continue;
+        }
+        let point_at = |distance: f32| Point2 {
+            x: start.x + dx * distance / length,
+            y: start.y + dy * distance / length,
+        };
+
+        let mut distance = 0.;
+        while distance < length {
+            let step = if phase < dash {
+                let step = (dash - phase).min(length - distance);
+                dashes.push([point_at(distance), point_at(distance + step)]);
+                step
+            } else {
+                (dash + gap - phase).min(length - distance)
+            };
+            distance += step;
+            phase = (phase + step) % (dash + gap);
+        }
+    }
+    dashes
+}
+
+/// Which paddle a ball bounces off given how far it is from the face of each paddle it touches.
+/// If it's touching both, the nearer face wins, with ties going to the left.
+fn nearest_contact(left: Option<f32>, right: Option<f32>) -> Option<Side> {
+    match (left, right) {
+        (Some(left), Some(right)) if right < left => Some(Side::Right),
+        (Some(_), _) => Some(Side::Left),
+        (None, Some(_)) => Some(Side::Right),
+        (None, None) => None,
+    }
+}
+
+/// The paddle on `side`, taken from the individual paddles so other fields stay borrowable.
+fn side_paddle_mut<'a>(left: &'a mut Paddle, right: &'a mut Paddle, side: Side) -> &'a mut Paddle {
+    match side {
+        Side::Left => left,
+        Side::Right => right,
+    }
+}
+
+/// Draws a white mesh at `dest` with the rotation, scale and color of `shape`, on top of an
+/// offset shadow. Purely cosmetic: the shadow doesn't take part in collisions.
+fn draw_with_shadow_at(
+    canvas: &mut graphics::Canvas,
+    mesh: &graphics::Mesh,
+    theme: &Theme,
+    dest: Point2<f32>,
+    shape: graphics::DrawParam,
+) {
+    canvas.draw(
+        mesh,
+        shape.dest(theme::shadow_dest(dest)).color(theme.shadow),
+    );
+    canvas.draw(mesh, shape.dest(dest));
+}
+
+/// Draws `text` centered on `center`.
+fn draw_centered_text(
+    canvas: &mut graphics::Canvas,
+    mut text: graphics::Text,
+    scale: f32,
+    center: Point2<f32>,
+    color: Color,
+) {
+    text.set_scale(scale);
+    text.set_layout(graphics::TextLayout::center());
+    canvas.draw(&text, graphics::DrawParam::new().dest(center).color(color));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ball::BALL_RADIUS;
+    use crate::harness::{FRAME_TIME, HEADLESS_HEIGHT, HEADLESS_WIDTH};
+    use crate::heat::CHARGED_POINTS;
+    use crate::paddle::{DASH_FRAMES, DASH_SPEED_MULTIPLIER};
+    use crate::settings::{PlayerControls, ServeRule};
+
+    /// A seeded match that's already on the field.
+    fn playing(settings: GameSettings) -> GameState {
+        let settings = GameSettings {
+            seed: Some(7),
+            ..settings
+        };
+        let mut state = GameState::with_bounds(HEADLESS_WIDTH, HEADLESS_HEIGHT, settings);
+        state.start_match(HEADLESS_WIDTH, HEADLESS_HEIGHT);
+        state
+    }
+
+    /// Sends the ball out through `side`'s wall and steps, conceding a point on that side.
+    fn concede(state: &mut GameState, side: Side) {
+        let (x, direction) = match side {
+            Side::Left => (-1., -1.),
+            Side::Right => (801., 1.),
+        };
+        state.ball.pos = Point2 { x, y: 300. };
+        state.ball.vel = Point2 {
+            x: direction * 5.,
+            y: 0.,
+        };
+        state.step(800., 600.).unwrap();
+    }
+
+    /// A frame where `keys` were just pressed and are held.
+    fn pressing(keys: &[KeyCode]) -> InputFrame {
+        InputFrame {
+            pressed: keys.to_vec(),
+            just_pressed: keys.to_vec(),
+            ..InputFrame::default()
+        }
+    }
+
+    #[test]
+    fn degenerate_bounds_leave_the_state_finite() {
+        let mut state = playing(GameSettings::default());
+        let ball = state.ball.pos;
+
+        state.reset_round(0., 0.);
+        assert_eq!(state.ball.pos, ball);
+        state
+            .simulate_frame(&InputFrame::default(), FRAME_TIME, 0., 0.)
+            .unwrap();
+        state.step(0., 0.).unwrap();
+
+        assert!(state.ball.pos.x.is_finite() && state.ball.pos.y.is_finite());
+        assert!(state.ball.has_finite_velocity());
+        for paddle in [state.left_paddle.pos, state.right_paddle.pos] {
+            assert!(paddle.x.is_finite() && paddle.y.is_finite());
+        }
+    }
+
+    #[test]
+    fn paddle_margin_sets_the_gap_to_each_wall() {
+        let margin = 45.;
+        let state = playing(GameSettings {
+            paddle_margin: margin,
+            ..GameSettings::default()
+        });
+
+        assert_eq!(state.left_paddle.pos.x, margin);
+        assert_eq!(state.right_paddle.pos.x, 800. - PADDLE_WIDTH - margin);
+    }
+
+    #[test]
+    fn practice_serve_key_launches_the_ball_its_way() {
+        let settings = GameSettings {
+            practice: true,
+            ..GameSettings::default()
+        };
+        let mut state = playing(settings);
+
+        for (key, direction) in [(KeyCode::Right, 1.), (KeyCode::Left, -1.)] {
+            state.delay_duration = std::time::Duration::from_secs(1);
+            state
+                .simulate_frame(
+                    &pressing(&[key]),
+                    FRAME_TIME,
+                    HEADLESS_WIDTH,
+                    HEADLESS_HEIGHT,
+                )
+                .unwrap();
+
+            assert!(state.delay_duration.is_zero());
+            assert_eq!(state.ball.vel.x.signum(), direction);
+        }
+    }
+
+    #[test]
+    fn goal_flashes_on_the_breached_side() {
+        let mut state = playing(GameSettings::default());
+
+        concede(&mut state, Side::Left);
+        assert_eq!(state.score, (0, 1));
+        assert!(matches!(state.goal_flash, Some((Side::Left, _))));
+
+        concede(&mut state, Side::Right);
+        assert!(matches!(state.goal_flash, Some((Side::Right, _))));
+    }
+
+    #[test]
+    fn conceding_costs_a_life_until_none_are_left() {
+        let mut state = playing(GameSettings {
+            elimination: true,
+            starting_lives: 2,
+            ..GameSettings::default()
+        });
+
+        concede(&mut state, Side::Left);
+        assert_eq!(state.lives, (1, 2));
+        assert_eq!(state.winner, None);
+
+        concede(&mut state, Side::Left);
+        assert_eq!(state.lives, (0, 2));
+        assert_eq!(state.winner, Some(Side::Right));
+    }
+
+    #[test]
+    fn headless_state_steps_without_a_context() {
+        let mut state = GameState::with_bounds(800., 600., GameSettings::default());
+        let before = state.ball.pos;
+
+        state.step(800., 600.).unwrap();
+
+        assert_eq!(state.ball.pos.x, before.x + state.ball.vel.x);
+        assert_eq!(state.score, (0, 0));
+    }
+
+    #[test]
+    fn ball_off_center_hits_only_the_taller_paddle() {
+        let mut state = playing(GameSettings {
+            left_paddle_height: 200.,
+            right_paddle_height: 60.,
+            ..GameSettings::default()
+        });
+        let y = 300. + 60.;
+
+        state.ball.pos = Point2 {
+            x: state.left_paddle.pos.x + PADDLE_WIDTH + BALL_RADIUS + 2.,
+            y,
+        };
+        state.ball.vel = Point2 { x: -5., y: 0. };
+        state.step(800., 600.).unwrap();
+        assert!(state.ball.vel.x > 0.);
+
+        state.ball.pos = Point2 {
+            x: state.right_paddle.pos.x - BALL_RADIUS - 2.,
+            y,
+        };
+        state.ball.vel = Point2 { x: 5., y: 0. };
+        state.step(800., 600.).unwrap();
+        assert!(state.ball.vel.x > 0.);
+    }
+
+    #[test]
+    fn go_flash_starts_when_the_delay_runs_out() {
+        let mut state = playing(GameSettings::default());
+        let idle = InputFrame::default();
+        state.delay_duration = FRAME_TIME * 2;
+
+        state
+            .simulate_frame(&idle, FRAME_TIME, HEADLESS_WIDTH, HEADLESS_HEIGHT)
+            .unwrap();
+        assert!(state.go_flash.is_zero());
+
+        state
+            .simulate_frame(&idle, FRAME_TIME, HEADLESS_WIDTH, HEADLESS_HEIGHT)
+            .unwrap();
+        assert!(state.delay_duration.is_zero());
+        assert_eq!(state.go_flash, GO_FLASH_DURATION);
+
+        state
+            .simulate_frame(&idle, FRAME_TIME, HEADLESS_WIDTH, HEADLESS_HEIGHT)
+            .unwrap();
+        assert_eq!(state.go_flash, GO_FLASH_DURATION - FRAME_TIME);
+    }
+
+    #[test]
+    fn paddles_move_during_the_delay_only_when_allowed() {
+        for allowed in [true, false] {
+            let mut state = playing(GameSettings {
+                move_during_delay: allowed,
+                ..GameSettings::default()
+            });
+            state.delay_duration = std::time::Duration::from_secs(1);
+            let start = state.left_paddle.pos.y;
+
+            state
+                .simulate_frame(
+                    &pressing(&[KeyCode::S]),
+                    FRAME_TIME,
+                    HEADLESS_WIDTH,
+                    HEADLESS_HEIGHT,
+                )
+                .unwrap();
+
+            assert_eq!(state.left_paddle.pos.y > start, allowed);
+            assert!(!state.delay_duration.is_zero());
+        }
+    }
+
+    #[test]
+    fn server_is_the_side_the_serve_travels_away_from() {
+        let mut state = playing(GameSettings {
+            practice: true,
+            ..GameSettings::default()
+        });
+
+        for _ in 0..10 {
+            state.reset_round(800., 600.);
+            let expected = if state.ball.vel.x >= 0. {
+                Side::Left
+            } else {
+                Side::Right
+            };
+            assert_eq!(state.server, expected);
+        }
+
+        for (key, server) in [(KeyCode::Left, Side::Right), (KeyCode::Right, Side::Left)] {
+            state.delay_duration = std::time::Duration::from_secs(1);
+            state
+                .simulate_frame(
+                    &pressing(&[key]),
+                    FRAME_TIME,
+                    HEADLESS_WIDTH,
+                    HEADLESS_HEIGHT,
+                )
+                .unwrap();
+            assert_eq!(state.server, server);
+        }
+    }
+
+    #[test]
+    fn zen_mode_serves_again_without_scoring() {
+        let mut state = playing(GameSettings {
+            zen: true,
+            ..GameSettings::default()
+        });
+
+        for side in [Side::Left, Side::Right] {
+            concede(&mut state, side);
+            assert_eq!(state.score, (0, 0));
+            assert_eq!(state.ball.pos.x, 400.);
+        }
+    }
+
+    #[test]
+    fn near_vertical_ball_gets_nudged_sideways() {
+        let mut state = playing(GameSettings::default());
+        state.ball.vel = Point2 { x: 0.1, y: 4. };
+        let speed = state.ball.speed();
+
+        for _ in 1..STUCK_BALL_FRAME_LIMIT {
+            state.check_stuck_ball();
+        }
+        assert_eq!(state.ball.vel.x, 0.1);
+
+        state.check_stuck_ball();
+        assert!(state.ball.vel.x >= STUCK_BALL_MIN_X_SPEED);
+        assert!((state.ball.speed() - speed).abs() < 1e-4);
+        assert_eq!(state.stuck_frames, 0);
+    }
+
+    #[test]
+    fn growing_ball_swells_with_the_rally_and_resets_on_a_point() {
+        let mut state = playing(GameSettings {
+            ball_growth: true,
+            ..GameSettings::default()
+        });
+        let start = state.ball_radius();
+
+        for _ in 0..3 {
+            state.record_paddle_hit(Side::Left);
+        }
+        assert!(state.ball_radius() > start);
+
+        concede(&mut state, Side::Left);
+        assert_eq!(state.rally_hits, 0);
+        assert_eq!(state.ball_radius(), start);
+    }
+
+    #[test]
+    fn ball_keeps_its_size_without_growth() {
+        let mut state = playing(GameSettings::default());
+        for _ in 0..3 {
+            state.record_paddle_hit(Side::Left);
+        }
+        assert_eq!(state.ball_radius(), BALL_RADIUS);
+    }
+
+    #[test]
+    fn ball_behind_the_left_paddle_scores_instead_of_bouncing() {
+        let mut state = playing(GameSettings::default());
+        let paddle = &state.left_paddle;
+        // Past the paddle's face, overlapping it and still heading for the goal
+        state.ball.pos = Point2 {
+            x: paddle.pos.x + PADDLE_WIDTH / 2.,
+            y: paddle.pos.y + paddle.height / 2.,
+        };
+        state.ball.vel = Point2 { x: -3., y: 0. };
+
+        state.step(800., 600.).unwrap();
+        assert!(state.ball.vel.x < 0.);
+        assert_eq!(state.rally_hits, 0);
+
+        for _ in 0..60 {
+            if state.score != (0, 0) {
+                break;
+            }
+            state.step(800., 600.).unwrap();
+        }
+        assert_eq!(state.score, (0, 1));
+    }
+
+    #[test]
+    fn summary_options_wrap_and_main_menu_leaves() {
+        let mut state = playing(GameSettings::default());
+        state.screen = Screen::Summary;
+
+        state.handle_input(&pressing(&[KeyCode::Up]), HEADLESS_WIDTH, HEADLESS_HEIGHT);
+        assert_eq!(
+            SummaryOption::ALL[state.summary_selection],
+            SummaryOption::MainMenu
+        );
+        state.handle_input(&pressing(&[KeyCode::Down]), HEADLESS_WIDTH, HEADLESS_HEIGHT);
+        assert_eq!(state.summary_selection, 0);
+
+        state.summary_selection = SummaryOption::ALL.len() - 1;
+        state.handle_input(
+            &pressing(&[KeyCode::Return]),
+            HEADLESS_WIDTH,
+            HEADLESS_HEIGHT,
+        );
+        assert_eq!(state.screen, Screen::Menu);
+    }
+
+    #[test]
+    fn rematch_resets_the_score_and_keeps_the_settings() {
+        let settings = GameSettings::builder()
+            .ball_speed(9.)
+            .starting_lives(4)
+            .build()
+            .unwrap();
+        let mut state = playing(settings);
+        let settings = state.settings.clone();
+        state.score = (11, 7);
+        state.winner = Some(Side::Left);
+        state.screen = Screen::Summary;
+
+        state.handle_input(
+            &pressing(&[KeyCode::Return]),
+            HEADLESS_WIDTH,
+            HEADLESS_HEIGHT,
+        );
+        assert_eq!(state.screen, Screen::Playing);
+        assert_eq!(state.score, (0, 0));
+        assert_eq!(state.winner, None);
+        assert_eq!(state.settings, settings);
+    }
+
+    #[test]
+    fn holding_both_directions_keeps_the_paddle_still() {
+        let mut state = playing(GameSettings::default());
+        let left_y = state.left_paddle.pos.y;
+        let both = pressing(&[KeyCode::W, KeyCode::S]);
+
+        for _ in 0..10 {
+            state
+                .simulate_frame(&both, FRAME_TIME, HEADLESS_WIDTH, HEADLESS_HEIGHT)
+                .unwrap();
+        }
+        assert_eq!(state.left_paddle.pos.y, left_y);
+    }
+
+    #[test]
+    fn unplugging_the_controller_pauses_until_it_returns() {
+        let mut state = playing(GameSettings::default());
+        let plugged_in = InputFrame {
+            controller_connected: true,
+            ..InputFrame::default()
+        };
+        state.handle_input(&plugged_in, HEADLESS_WIDTH, HEADLESS_HEIGHT);
+        assert!(!state.paused);
+
+        state.handle_input(&InputFrame::default(), HEADLESS_WIDTH, HEADLESS_HEIGHT);
+        assert!(state.paused && state.controller_lost);
+
+        // Space can't resume while the controller is missing
+        state.handle_input(
+            &pressing(&[KeyCode::Space]),
+            HEADLESS_WIDTH,
+            HEADLESS_HEIGHT,
+        );
+        assert!(state.paused);
+
+        state.handle_input(&plugged_in, HEADLESS_WIDTH, HEADLESS_HEIGHT);
+        assert!(!state.paused && !state.controller_lost);
+    }
+
+    #[test]
+    fn fresh_serve_ramps_up_to_full_speed() {
+        let mut state = playing(GameSettings {
+            serve_grace: true,
+            ..GameSettings::default()
+        });
+        let idle = InputFrame::default();
+        state.delay_duration = FRAME_TIME;
+        state
+            .simulate_frame(&idle, FRAME_TIME, HEADLESS_WIDTH, HEADLESS_HEIGHT)
+            .unwrap();
+        assert!((state.serve_speed_multiplier() - SERVE_GRACE_START_SPEED).abs() < 1e-4);
+
+        let mut previous = state.serve_speed_multiplier();
+        while !state.serve_grace.is_zero() {
+            state
+                .simulate_frame(&idle, FRAME_TIME, HEADLESS_WIDTH, HEADLESS_HEIGHT)
+                .unwrap();
+            assert!(state.serve_speed_multiplier() > previous);
+            previous = state.serve_speed_multiplier();
+        }
+        assert_eq!(state.serve_speed_multiplier(), 1.);
+    }
+
+    #[test]
+    fn first_server_picked_on_the_menu_serves_the_opening_ball() {
+        for (key, side, x_direction) in [
+            (KeyCode::Left, Side::Left, 1.),
+            (KeyCode::Right, Side::Right, -1.),
+        ] {
+            let settings = GameSettings {
+                seed: Some(7),
+                ..GameSettings::default()
+            };
+            let mut state = GameState::with_bounds(HEADLESS_WIDTH, HEADLESS_HEIGHT, settings);
+            state.handle_input(&pressing(&[key]), HEADLESS_WIDTH, HEADLESS_HEIGHT);
+            assert_eq!(state.first_server, Some(side));
+
+            state.handle_input(
+                &pressing(&[KeyCode::Return]),
+                HEADLESS_WIDTH,
+                HEADLESS_HEIGHT,
+            );
+            assert_eq!(state.screen, Screen::Playing);
+            assert_eq!(state.server, side);
+            assert_eq!(state.ball.vel.x.signum(), x_direction);
+        }
+    }
+
+    #[test]
+    fn picking_the_same_first_server_again_goes_back_to_random() {
+        let mut state =
+            GameState::with_bounds(HEADLESS_WIDTH, HEADLESS_HEIGHT, GameSettings::default());
+        let left = pressing(&[KeyCode::Left]);
+        state.handle_input(&left, HEADLESS_WIDTH, HEADLESS_HEIGHT);
+        state.handle_input(&left, HEADLESS_WIDTH, HEADLESS_HEIGHT);
+        assert_eq!(state.first_server, None);
+    }
+
+    #[test]
+    fn ball_count_follows_spawns_and_removals() {
+        let mut state = playing(GameSettings {
+            multi_ball: true,
+            max_balls: 3,
+            ..GameSettings::default()
+        });
+        assert_eq!(state.balls_in_play(), 1);
+
+        for _ in 0..5 {
+            state.spawn_extra_ball(HEADLESS_WIDTH, HEADLESS_HEIGHT);
+            assert_eq!(state.balls_in_play(), 1 + state.extra_balls.len());
+        }
+        assert_eq!(state.balls_in_play(), 3);
+
+        state.extra_balls[0].pos.x = -HEADLESS_WIDTH * 10.;
+        state.step(HEADLESS_WIDTH, HEADLESS_HEIGHT).unwrap();
+        assert_eq!(state.extra_balls.len(), 1);
+        assert_eq!(state.balls_in_play(), 2);
+    }
+
+    #[test]
+    fn switching_to_the_ai_keeps_the_paddle_in_place() {
+        let mut state = playing(GameSettings::default());
+        state.right_paddle.pos.y = 0.;
+        // Heading away from the AI, so it has nothing to chase yet
+        state.ball.vel = Point2 { x: -4., y: 0. };
+
+        state.handle_input(
+            &pressing(&[KeyCode::Space]),
+            HEADLESS_WIDTH,
+            HEADLESS_HEIGHT,
+        );
+        state.handle_input(&pressing(&[KeyCode::A]), HEADLESS_WIDTH, HEADLESS_HEIGHT);
+        assert_eq!(state.settings.opponent, Opponent::Ai);
+        assert_eq!(state.ai.target_y, state.right_paddle.center_y());
+
+        state.handle_input(
+            &pressing(&[KeyCode::Space]),
+            HEADLESS_WIDTH,
+            HEADLESS_HEIGHT,
+        );
+        state
+            .simulate_frame(
+                &InputFrame::default(),
+                FRAME_TIME,
+                HEADLESS_WIDTH,
+                HEADLESS_HEIGHT,
+            )
+            .unwrap();
+        assert_eq!(state.right_paddle.pos.y, 0.);
+    }
+
+    #[test]
+    fn escaped_ball_is_served_again_without_a_point() {
+        let mut state = playing(GameSettings::default());
+        state.ball.pos = Point2 {
+            x: -HEADLESS_WIDTH * 10.,
+            y: HEADLESS_HEIGHT / 2.,
+        };
+
+        state.step(HEADLESS_WIDTH, HEADLESS_HEIGHT).unwrap();
+        assert_eq!(
+            state.ball.pos,
+            Point2 {
+                x: HEADLESS_WIDTH / 2.,
+                y: HEADLESS_HEIGHT / 2.,
+            }
+        );
+        assert_eq!(state.score, (0, 0));
+    }
+
+    #[test]
+    fn each_players_keys_drive_only_their_paddle() {
+        let controls = vec![
+            PlayerControls::new(Side::Left, KeyCode::W, KeyCode::S, KeyCode::D, KeyCode::Q),
+            PlayerControls::new(
+                Side::Right,
+                KeyCode::Up,
+                KeyCode::Down,
+                KeyCode::Left,
+                KeyCode::RShift,
+            ),
+            PlayerControls::new(Side::Right, KeyCode::I, KeyCode::K, KeyCode::J, KeyCode::U),
+        ];
+
+        for (key, side, direction) in [
+            (KeyCode::S, Side::Left, 1.),
+            (KeyCode::Up, Side::Right, -1.),
+            (KeyCode::K, Side::Right, 1.),
+        ] {
+            let mut state = playing(GameSettings {
+                controls: controls.clone(),
+                ..GameSettings::default()
+            });
+            let before = (state.left_paddle.pos.y, state.right_paddle.pos.y);
+            state
+                .simulate_frame(
+                    &pressing(&[key]),
+                    FRAME_TIME,
+                    HEADLESS_WIDTH,
+                    HEADLESS_HEIGHT,
+                )
+                .unwrap();
+            let after = (state.left_paddle.pos.y, state.right_paddle.pos.y);
+
+            let (moved, still) = match side {
+                Side::Left => ((before.0, after.0), (before.1, after.1)),
+                Side::Right => ((before.1, after.1), (before.0, after.0)),
+            };
+            assert_eq!((moved.1 - moved.0).signum(), direction, "{key:?}");
+            assert_eq!(still.0, still.1, "{key:?}");
+        }
+    }
+
+    #[test]
+    fn rewinding_back_and_forward_shows_the_same_frame() {
+        let mut state = playing(GameSettings::default());
+        state.enable_rewind();
+        for _ in 0..5 {
+            state
+                .simulate_frame(
+                    &InputFrame::default(),
+                    FRAME_TIME,
+                    HEADLESS_WIDTH,
+                    HEADLESS_HEIGHT,
+                )
+                .unwrap();
+            let snapshot = state.snapshot();
+            state.rewind.as_mut().unwrap().record(snapshot);
+        }
+        let newest = state.snapshot();
+
+        let input = |state: &mut GameState, key| {
+            state.handle_input(&pressing(&[key]), HEADLESS_WIDTH, HEADLESS_HEIGHT)
+        };
+        input(&mut state, KeyCode::Space);
+        for _ in 0..3 {
+            input(&mut state, KeyCode::Comma);
+        }
+        assert_ne!(state.snapshot(), newest);
+        for _ in 0..3 {
+            input(&mut state, KeyCode::Period);
+        }
+        assert_eq!(state.snapshot(), newest);
+
+        // Unpausing from an older frame carries on from the newest one
+        input(&mut state, KeyCode::Comma);
+        input(&mut state, KeyCode::Space);
+        assert_eq!(state.snapshot(), newest);
+    }
+
+    #[test]
+    fn ball_waits_for_the_servers_key() {
+        let mut state = playing(GameSettings {
+            serve_on_keypress: true,
+            ..GameSettings::default()
+        });
+        concede(&mut state, Side::Left);
+        assert_eq!(state.delay_duration, std::time::Duration::MAX);
+
+        let idle = InputFrame::default();
+        let start = state.ball.pos;
+        for _ in 0..120 {
+            state
+                .simulate_frame(&idle, FRAME_TIME, HEADLESS_WIDTH, HEADLESS_HEIGHT)
+                .unwrap();
+        }
+        assert_eq!(state.ball.pos, start);
+
+        let serve = state
+            .settings
+            .controls
+            .iter()
+            .find(|controls| controls.side == state.server)
+            .unwrap()
+            .serve;
+        state
+            .simulate_frame(
+                &pressing(&[serve]),
+                FRAME_TIME,
+                HEADLESS_WIDTH,
+                HEADLESS_HEIGHT,
+            )
+            .unwrap();
+        assert!(state.delay_duration.is_zero());
+        state
+            .simulate_frame(&idle, FRAME_TIME, HEADLESS_WIDTH, HEADLESS_HEIGHT)
+            .unwrap();
+        assert_ne!(state.ball.pos, start);
+    }
+
+    #[test]
+    fn trailing_player_gets_a_bigger_paddle_until_they_score() {
+        let mut state = playing(GameSettings {
+            comeback_margin: Some(3),
+            ..GameSettings::default()
+        });
+        let normal = state.left_paddle.height;
+
+        for _ in 0..3 {
+            concede(&mut state, Side::Left);
+        }
+        assert_eq!(state.comeback, Some(Side::Left));
+        assert!(state.left_paddle.height > normal);
+        assert_eq!(state.right_paddle.height, normal);
+
+        concede(&mut state, Side::Right);
+        assert_eq!(state.comeback, None);
+        assert_eq!(state.left_paddle.height, normal);
+    }
+
+    #[test]
+    fn seeded_coin_flip_always_lands_the_same_way() {
+        let flip = |seed| {
+            let settings = GameSettings {
+                coin_flip: true,
+                seed: Some(seed),
+                ..GameSettings::default()
+            };
+            let mut state = GameState::with_bounds(HEADLESS_WIDTH, HEADLESS_HEIGHT, settings);
+            state.start_match(HEADLESS_WIDTH, HEADLESS_HEIGHT);
+            let (result, _) = state.coin_flip.expect("the coin should be flipped");
+            assert_eq!(state.server, result);
+            result
+        };
+
+        for seed in 0..10 {
+            assert_eq!(flip(seed), flip(seed));
+        }
+        let results: Vec<Side> = (0..32).map(flip).collect();
+        assert!(results.contains(&Side::Left) && results.contains(&Side::Right));
+    }
+
+    #[cfg(not(feature = "audio"))]
+    #[test]
+    fn plays_and_scores_without_audio() {
+        let mut state = playing(GameSettings::default());
+        let start = state.ball.pos;
+        state
+            .simulate_frame(
+                &InputFrame::default(),
+                FRAME_TIME,
+                HEADLESS_WIDTH,
+                HEADLESS_HEIGHT,
+            )
+            .unwrap();
+        assert_ne!(state.ball.pos, start);
+
+        concede(&mut state, Side::Left);
+        assert_eq!(state.score, (0, 1));
+        assert!(
+            state
+                .take_events()
+                .iter()
+                .any(|timed| timed.event == GameEvent::Scored(Side::Right))
+        );
+    }
+
+    #[test]
+    fn dash_speeds_the_paddle_up_then_recharges() {
+        let mut state = playing(GameSettings::default());
+        let frame = |state: &mut GameState, input: &InputFrame| {
+            let before = state.left_paddle.pos.y;
+            state
+                .simulate_frame(input, FRAME_TIME, HEADLESS_WIDTH, HEADLESS_HEIGHT)
+                .unwrap();
+            state.left_paddle.pos.y - before
+        };
+        let held = InputFrame {
+            pressed: vec![KeyCode::S],
+            ..InputFrame::default()
+        };
+        let dash = pressing(&[KeyCode::S, KeyCode::LShift]);
+        let speed = state.settings.paddle_speed;
+        let dashing = speed * DASH_SPEED_MULTIPLIER;
+
+        assert_eq!(frame(&mut state, &dash), dashing);
+        for _ in 1..DASH_FRAMES {
+            assert_eq!(frame(&mut state, &held), dashing);
+        }
+        assert_eq!(frame(&mut state, &held), speed);
+
+        // Still recharging, so another press does nothing
+        assert_eq!(frame(&mut state, &dash), speed);
+    }
+
+    #[test]
+    fn dashing_paddle_stays_inside_the_field() {
+        let mut state = playing(GameSettings::default());
+        state.left_paddle.pos.y = HEADLESS_HEIGHT - state.left_paddle.height - 1.;
+        state
+            .simulate_frame(
+                &pressing(&[KeyCode::S, KeyCode::LShift]),
+                FRAME_TIME,
+                HEADLESS_WIDTH,
+                HEADLESS_HEIGHT,
+            )
+            .unwrap();
+        assert_eq!(
+            state.left_paddle.pos.y,
+            HEADLESS_HEIGHT - state.left_paddle.height
+        );
+    }
+
+    #[test]
+    fn charged_heat_meter_scores_double() {
+        let mut state = playing(GameSettings {
+            heat_meter: true,
+            ..GameSettings::default()
+        });
+        for _ in 0..10 {
+            state.record_paddle_hit(Side::Left);
+        }
+        concede(&mut state, Side::Right);
+        assert_eq!(state.score, (CHARGED_POINTS, 0));
+
+        state.record_paddle_hit(Side::Left);
+        concede(&mut state, Side::Right);
+        assert_eq!(state.score, (CHARGED_POINTS + 1, 0));
+    }
+
+    #[test]
+    fn nearer_paddle_face_wins_a_double_contact() {
+        assert_eq!(nearest_contact(Some(3.), Some(1.)), Some(Side::Right));
+        assert_eq!(nearest_contact(Some(1.), Some(3.)), Some(Side::Left));
+        assert_eq!(nearest_contact(Some(2.), Some(2.)), Some(Side::Left));
+        assert_eq!(nearest_contact(None, Some(2.)), Some(Side::Right));
+        assert_eq!(nearest_contact(None, None), None);
+    }
+
+    #[test]
+    fn settings_window_scrolls_to_keep_the_selection_in_view() {
+        let len = SettingsItem::ALL.len();
+        assert_eq!(scroll_window(0, len, 13), 0..13);
+        assert_eq!(scroll_window(20, len, 13), 14..27);
+        assert_eq!(scroll_window(len - 1, len, 13), len - 13..len);
+        // A list shorter than the window is shown whole
+        assert_eq!(scroll_window(2, 5, 13), 0..5);
+    }
+
+    #[test]
+    fn ball_touching_both_paddles_bounces_once() {
+        let mut state = playing(GameSettings::default());
+        let radius = state.ball_radius();
+        let left_surface_x = state.left_paddle.rect().right();
+        state.ball.pos = Point2 {
+            x: left_surface_x + radius + 1.,
+            y: state.left_paddle.center_y(),
+        };
+        // Moving into the left paddle this frame
+        state.ball.vel = Point2 { x: -2., y: 0. };
+        // Squeeze the right paddle in so the ball overlaps it too
+        state.right_paddle.pos.x = left_surface_x + 2. * radius - 5.;
+        state.right_paddle.pos.y = state.left_paddle.pos.y;
+        state.take_events();
+
+        state.step(HEADLESS_WIDTH, HEADLESS_HEIGHT).unwrap();
+        let hits: Vec<GameEvent> = state
+            .take_events()
+            .into_iter()
+            .map(|timed| timed.event)
+            .filter(|event| matches!(event, GameEvent::PaddleHit(_)))
+            .collect();
+        assert_eq!(hits, vec![GameEvent::PaddleHit(Side::Left)]);
+        assert_eq!(state.rally_hits, 1);
+        assert!(state.ball.vel.x > 0.);
+    }
+
+    #[test]
+    fn menu_board_choice_lays_the_field_out_for_its_size() {
+        let mut state =
+            GameState::with_bounds(HEADLESS_WIDTH, HEADLESS_HEIGHT, GameSettings::default());
+        state.handle_input(&pressing(&[KeyCode::B]), HEADLESS_WIDTH, HEADLESS_HEIGHT);
+        assert_eq!(state.settings.board, BoardPreset::Wide);
+
+        let (width, height) = state.settings.board.window_size();
+        state.fit_to_bounds(width, height);
+        state.handle_input(&pressing(&[KeyCode::Return]), width, height);
+        assert_eq!(
+            state.right_paddle.rect().right(),
+            width - state.settings.paddle_margin
+        );
+        assert_eq!(
+            state.ball.pos,
+            Point2 {
+                x: width / 2.,
+                y: height / 2.
+            }
+        );
+    }
+
+    #[test]
+    fn serve_follows_the_point_under_the_winner_and_loser_rules() {
+        // Under "to loser" the scorer serves, under "to winner" the player who conceded does
+        for (serve_rule, first_server, second_server) in [
+            (ServeRule::ToLoser, Side::Right, Side::Left),
+            (ServeRule::ToWinner, Side::Left, Side::Right),
+        ] {
+            let mut state = playing(GameSettings {
+                serve_rule,
+                ..GameSettings::default()
+            });
+
+            concede(&mut state, Side::Left);
+            assert_eq!(state.server, first_server, "{serve_rule:?}");
+            concede(&mut state, Side::Right);
+            assert_eq!(state.server, second_server, "{serve_rule:?}");
+        }
+    }
+
+    #[test]
+    fn server_alternates_under_the_alternate_rule() {
+        let mut state = playing(GameSettings {
+            serve_rule: ServeRule::AlternateSides,
+            ..GameSettings::default()
+        });
+        let first = state.server;
+
+        concede(&mut state, Side::Left);
+        assert_eq!(state.server, first.opposite());
+        concede(&mut state, Side::Right);
+        assert_eq!(state.server, first);
+        concede(&mut state, Side::Right);
+        assert_eq!(state.server, first.opposite());
+    }
+
+    #[test]
+    fn later_events_carry_later_timestamps() {
+        let mut state = playing(GameSettings::default());
+        let idle = InputFrame::default();
+
+        concede(&mut state, Side::Left);
+        for _ in 0..30 {
+            state
+                .simulate_frame(&idle, FRAME_TIME, HEADLESS_WIDTH, HEADLESS_HEIGHT)
+                .unwrap();
+        }
+        concede(&mut state, Side::Right);
+
+        let times: Vec<std::time::Duration> = state
+            .take_events()
+            .into_iter()
+            .filter(|timed| matches!(timed.event, GameEvent::Scored(_)))
+            .map(|timed| timed.time)
+            .collect();
+        assert_eq!(times.len(), 2);
+        assert!(times[1] > times[0]);
+        assert_eq!(times[1], state.match_time);
+    }
+
+    #[test]
+    fn wall_bounce_loses_energy_below_full_restitution() {
+        let mut state = playing(GameSettings {
+            restitution: 0.8,
+            ..GameSettings::default()
+        });
+        state.ball.pos = Point2 {
+            x: HEADLESS_WIDTH / 2.,
+            y: state.ball_radius() + 1.,
+        };
+        state.ball.vel = Point2 { x: 3., y: -4. };
+        state.take_events();
+
+        state.step(HEADLESS_WIDTH, HEADLESS_HEIGHT).unwrap();
+        assert!(
+            state
+                .take_events()
+                .iter()
+                .any(|timed| timed.event == GameEvent::WallBounce)
+        );
+        assert!(state.ball.vel.y > 0.);
+        assert!((state.ball.speed() - 4.).abs() < 1e-4);
+    }
+
+    #[test]
+    fn small_window_shrinks_the_paddles_and_ball() {
+        let mut state = playing(GameSettings {
+            scale_to_window: true,
+            ..GameSettings::default()
+        });
+        let (height, width, radius) = (
+            state.left_paddle.height,
+            state.left_paddle.width,
+            state.ball_radius(),
+        );
+
+        state.fit_to_bounds(320., 240.);
+        assert_eq!(state.left_paddle.height, height / 2.);
+        assert_eq!(state.right_paddle.height, height / 2.);
+        assert_eq!(state.left_paddle.width, width / 2.);
+        assert_eq!(state.ball_radius(), radius / 2.);
+    }
+
+    #[test]
+    fn match_clock_stops_while_paused() {
+        let mut state = playing(GameSettings::default());
+        let idle = InputFrame::default();
+        let frame = |state: &mut GameState| {
+            state
+                .simulate_frame(&idle, FRAME_TIME, HEADLESS_WIDTH, HEADLESS_HEIGHT)
+                .unwrap()
+        };
+
+        frame(&mut state);
+        assert_eq!(state.match_time, FRAME_TIME);
+
+        state.handle_input(
+            &pressing(&[KeyCode::Space]),
+            HEADLESS_WIDTH,
+            HEADLESS_HEIGHT,
+        );
+        for _ in 0..10 {
+            frame(&mut state);
+        }
+        assert_eq!(state.match_time, FRAME_TIME);
+
+        state.handle_input(
+            &pressing(&[KeyCode::Space]),
+            HEADLESS_WIDTH,
+            HEADLESS_HEIGHT,
+        );
+        frame(&mut state);
+        assert_eq!(state.match_time, FRAME_TIME * 2);
+    }
+
+    #[test]
+    fn fair_serve_sends_the_first_ball_to_the_human() {
+        let mut state = playing(GameSettings {
+            fair_serve: true,
+            opponent: Opponent::Ai,
+            ..GameSettings::default()
+        });
+        state.start_match(HEADLESS_WIDTH, HEADLESS_HEIGHT);
+        assert_eq!(state.server, Side::Right);
+        assert!(state.ball.vel.x < 0.);
+
+        // After that the ball goes to whoever lost the point
+        concede(&mut state, Side::Right);
+        assert!(state.ball.vel.x > 0.);
+    }
+
+    #[test]
+    fn menu_starts_the_selected_mode() {
+        let mut state =
+            GameState::with_bounds(HEADLESS_WIDTH, HEADLESS_HEIGHT, GameSettings::default());
+        let practice = GameMode::ALL
+            .iter()
+            .position(|mode| *mode == GameMode::Practice)
+            .unwrap();
+        for _ in 0..practice {
+            state.handle_input(&pressing(&[KeyCode::Down]), HEADLESS_WIDTH, HEADLESS_HEIGHT);
+        }
+        state.handle_input(
+            &pressing(&[KeyCode::Return]),
+            HEADLESS_WIDTH,
+            HEADLESS_HEIGHT,
+        );
+
+        assert_eq!(state.screen, Screen::Playing);
+        assert!(state.settings.practice);
+    }
+
+    #[test]
+    fn both_players_ready_starts_the_countdown() {
+        let mut settings = GameSettings {
+            ready_check: true,
+            ..GameSettings::default()
+        };
+        settings.controls[1] = PlayerControls::new(
+            Side::Right,
+            KeyCode::Up,
+            KeyCode::Down,
+            KeyCode::Return,
+            KeyCode::RShift,
+        );
+        let mut state = playing(settings);
+        let start = state.ball.pos;
+        let frame = |state: &mut GameState, keys: &[KeyCode]| {
+            state
+                .simulate_frame(&pressing(keys), FRAME_TIME, HEADLESS_WIDTH, HEADLESS_HEIGHT)
+                .unwrap()
+        };
+        assert_eq!(state.ready, Some((false, false)));
+
+        frame(&mut state, &[KeyCode::W]);
+        assert_eq!(state.ready, Some((true, false)));
+        frame(&mut state, &[]);
+        assert_eq!(state.ball.pos, start);
+
+        frame(&mut state, &[KeyCode::Up]);
+        assert_eq!(state.ready, None);
+        // The countdown begins on the frame the second player readies up
+        assert_eq!(
+            state.delay_duration + FRAME_TIME,
+            state.settings.serve_delay
+        );
+        assert_eq!(state.ball.pos, start);
+    }
+
+    #[test]
+    fn left_return_makes_left_the_last_hitter_until_the_next_serve() {
+        let mut state = playing(GameSettings::default());
+        assert_eq!(state.ball.last_hitter, None);
+
+        state.ball.pos = Point2 {
+            x: state.left_paddle.rect().right() + 10.,
+            y: state.left_paddle.center_y(),
+        };
+        state.ball.vel = Point2 { x: -5., y: 0. };
+        state.step(HEADLESS_WIDTH, HEADLESS_HEIGHT).unwrap();
+        assert_eq!(state.ball.last_hitter, Some(Side::Left));
+        assert_eq!(
+            state.theme.ball_color(state.ball.last_hitter),
+            state.theme.accents.0
+        );
+
+        concede(&mut state, Side::Right);
+        assert_eq!(state.ball.last_hitter, None);
+    }
+
+    #[test]
+    fn deciding_point_fires_the_win_and_starts_the_celebration() {
+        let mut state = playing(
+            GameSettings::builder()
+                .elimination(true)
+                .starting_lives(2)
+                .build()
+                .unwrap(),
+        );
+        let match_won = |state: &mut GameState| {
+            state
+                .take_events()
+                .iter()
+                .any(|timed| matches!(timed.event, GameEvent::MatchWon { .. }))
+        };
+
+        concede(&mut state, Side::Left);
+        assert!(!match_won(&mut state));
+        assert_eq!(state.celebration, None);
+
+        concede(&mut state, Side::Left);
+        assert!(match_won(&mut state));
+        assert_eq!(state.winner, Some(Side::Right));
+        assert_eq!(state.celebration, Some(CELEBRATION_DURATION));
+
+        // Nothing interrupts the celebration
+        state.handle_input(
+            &pressing(&[KeyCode::Space]),
+            HEADLESS_WIDTH,
+            HEADLESS_HEIGHT,
+        );
+        assert!(!state.paused);
+    }
+
+    #[test]
+    fn doubles_point_leaves_the_other_ball_in_flight() {
+        let mut state = playing(GameSettings {
+            doubles: true,
+            ..GameSettings::default()
+        });
+        assert_eq!(state.extra_balls.len(), 1);
+        let mut other = state.extra_balls[0];
+        other.pos = Point2 { x: 300., y: 200. };
+        other.vel = Point2 { x: 3., y: 1. };
+        state.extra_balls[0] = other;
+
+        concede(&mut state, Side::Left);
+        assert_eq!(state.score, (0, 1));
+        assert_eq!(state.extra_balls.len(), 1);
+        assert_eq!(state.extra_balls[0].pos, Point2 { x: 303., y: 201. });
+        assert_eq!(state.extra_balls[0].vel, other.vel);
+        // The ball that scored is straight back in play
+        assert_eq!(
+            state.ball.pos,
+            Point2 {
+                x: HEADLESS_WIDTH / 2.,
+                y: HEADLESS_HEIGHT / 2.,
+            }
+        );
+    }
+
+    #[test]
+    fn redo_recenters_the_round_and_keeps_the_score() {
+        let mut state = playing(GameSettings::default());
+        concede(&mut state, Side::Left);
+        state.delay_duration = std::time::Duration::ZERO;
+        state.ball.pos = Point2 { x: 120., y: 80. };
+        state.left_paddle.pos.y = 0.;
+
+        let redo = pressing(&[KeyCode::R]);
+        state
+            .simulate_frame(&redo, FRAME_TIME, HEADLESS_WIDTH, HEADLESS_HEIGHT)
+            .unwrap();
+        assert_eq!(state.score, (0, 1));
+        assert_eq!(
+            state.ball.pos,
+            Point2 {
+                x: HEADLESS_WIDTH / 2.,
+                y: HEADLESS_HEIGHT / 2.,
+            }
+        );
+        assert_eq!(state.left_paddle.center_y(), HEADLESS_HEIGHT / 2.);
+        assert!(!state.delay_duration.is_zero());
+
+        // Redoing again straight away is ignored
+        state.ball.pos = Point2 { x: 120., y: 80. };
+        state
+            .simulate_frame(&redo, FRAME_TIME, HEADLESS_WIDTH, HEADLESS_HEIGHT)
+            .unwrap();
+        assert_eq!(state.ball.pos, Point2 { x: 120., y: 80. });
+    }
+
+    #[test]
+    fn drawables_cover_everything_on_the_field() {
+        let count = |state: &GameState| {
+            let (mut paddles, mut obstacles, mut balls) = (0, 0, 0);
+            for drawable in state.drawables() {
+                match drawable {
+                    Drawable::Paddle { .. } => paddles += 1,
+                    Drawable::Obstacle { .. } => obstacles += 1,
+                    Drawable::Ball { .. } => balls += 1,
+                    Drawable::PowerUp { .. } => {}
+                }
+            }
+            (paddles, obstacles, balls)
+        };
+
+        let state = playing(GameSettings::default());
+        assert_eq!(count(&state), (2, 0, 1));
+
+        let state = playing(GameSettings {
+            obstacle_count: 3,
+            doubles: true,
+            ..GameSettings::default()
+        });
+        assert_eq!(count(&state), (2, state.obstacles.len(), 2));
+        assert!(!state.obstacles.is_empty());
+    }
+
+    #[test]
+    fn big_deficit_brings_out_the_mercy_ball() {
+        let mut state = playing(GameSettings {
+            mercy_ball: Some(3),
+            ..GameSettings::default()
+        });
+        let speed = state.settings.ball_speed;
+        state.score = (0, 2);
+        for _ in 0..100 {
+            state.serve_from_center(HEADLESS_WIDTH, HEADLESS_HEIGHT);
+            state.maybe_serve_mercy_ball(Side::Right);
+            assert!(!state.ball.is_mercy());
+        }
+
+        state.score = (0, 3);
+        let mercy = (0..100).find_map(|_| {
+            state.serve_from_center(HEADLESS_WIDTH, HEADLESS_HEIGHT);
+            state.maybe_serve_mercy_ball(Side::Right);
+            state.ball.is_mercy().then_some(state.ball)
+        });
+        let mercy = mercy.expect("a mercy ball should turn up");
+        assert!(mercy.speed() < speed);
+        assert!(mercy.arc > 0.);
+    }
+
+    #[test]
+    fn untouched_ball_is_served_again_after_the_timeout() {
+        let timeout = FRAME_TIME * 5;
+        let mut state = playing(GameSettings {
+            dead_ball_timeout: Some(timeout),
+            ..GameSettings::default()
+        });
+        let idle = InputFrame::default();
+        let center = Point2 {
+            x: HEADLESS_WIDTH / 2.,
+            y: HEADLESS_HEIGHT / 2.,
+        };
+        // Bouncing up and down in the middle, where nobody can reach it
+        state.ball.vel = Point2 { x: 0.6, y: 4. };
+
+        for _ in 0..4 {
+            state
+                .simulate_frame(&idle, FRAME_TIME, HEADLESS_WIDTH, HEADLESS_HEIGHT)
+                .unwrap();
+        }
+        assert_ne!(state.ball.pos, center);
+
+        state
+            .simulate_frame(&idle, FRAME_TIME, HEADLESS_WIDTH, HEADLESS_HEIGHT)
+            .unwrap();
+        assert_eq!(state.ball.pos, center);
+        assert_eq!(state.score, (0, 0));
+        assert!(!state.delay_duration.is_zero());
+    }
+
+    #[test]
+    fn doubling_the_sensitivity_doubles_analog_movement() {
+        let stick = InputFrame {
+            controller_connected: true,
+            controller_axis: 0.4,
+            ..InputFrame::default()
+        };
+        let displacement = |sensitivity| {
+            let mut state = playing(GameSettings {
+                sensitivity,
+                ..GameSettings::default()
+            });
+            let before = state.left_paddle.pos.y;
+            state
+                .simulate_frame(&stick, FRAME_TIME, HEADLESS_WIDTH, HEADLESS_HEIGHT)
+                .unwrap();
+            state.left_paddle.pos.y - before
+        };
+
+        let single = displacement(1.);
+        assert!(single > 0.);
+        assert!((displacement(2.) - 2. * single).abs() < 1e-4);
+    }
+
+    #[test]
+    fn linked_paddles_move_together_and_independent_ones_apart() {
+        let down = pressing(&[KeyCode::S]);
+        let moved = |paddle_link| {
+            let mut state = playing(GameSettings {
+                paddle_link,
+                ..GameSettings::default()
+            });
+            let (left_y, right_y) = (state.left_paddle.pos.y, state.right_paddle.pos.y);
+            state.handle_input(&down, HEADLESS_WIDTH, HEADLESS_HEIGHT);
+            state
+                .simulate_frame(&down, FRAME_TIME, HEADLESS_WIDTH, HEADLESS_HEIGHT)
+                .unwrap();
+            (
+                state.left_paddle.pos.y - left_y,
+                state.right_paddle.pos.y - right_y,
+            )
+        };
+
+        let (left, right) = moved(PaddleLink::Linked);
+        assert!(left > 0.);
+        assert_eq!(left, right);
+
+        let (left, right) = moved(PaddleLink::Independent);
+        assert!(left > 0.);
+        assert_eq!(right, 0.);
+    }
+
+    #[test]
+    fn scrubbing_the_results_restores_the_recorded_frame() {
+        let mut state = playing(GameSettings::default());
+        let mut frames = Vec::new();
+        for _ in 0..10 {
+            state.step(HEADLESS_WIDTH, HEADLESS_HEIGHT).unwrap();
+            frames.push(state.snapshot());
+            state.recording.record(state.snapshot(), state.rally_hits);
+        }
+        state.screen = Screen::Summary;
+
+        let left = pressing(&[KeyCode::Left]);
+        for _ in 0..3 {
+            state.handle_input(&left, HEADLESS_WIDTH, HEADLESS_HEIGHT);
+        }
+        assert_eq!(state.snapshot(), frames[6]);
+        state.handle_input(
+            &pressing(&[KeyCode::Right]),
+            HEADLESS_WIDTH,
+            HEADLESS_HEIGHT,
+        );
+        assert_eq!(state.snapshot(), frames[7]);
+    }
+    #[test]
+    fn space_toggles_pause() {
+        let mut state = playing(GameSettings::default());
+        let space = pressing(&[KeyCode::Space]);
+
+        state.handle_input(&space, HEADLESS_WIDTH, HEADLESS_HEIGHT);
+        assert!(state.paused);
+        state.handle_input(&space, HEADLESS_WIDTH, HEADLESS_HEIGHT);
+        assert!(!state.paused);
+    }
+    #[test]
+    fn held_key_moves_only_its_paddle() {
+        let mut state = playing(GameSettings::default());
+        let (left_y, right_y) = (state.left_paddle.pos.y, state.right_paddle.pos.y);
+        let down = pressing(&[KeyCode::S]);
+
+        state.handle_input(&down, HEADLESS_WIDTH, HEADLESS_HEIGHT);
+        state
+            .simulate_frame(&down, FRAME_TIME, HEADLESS_WIDTH, HEADLESS_HEIGHT)
+            .unwrap();
+
+        assert!(state.left_paddle.pos.y > left_y);
+        assert_eq!(state.right_paddle.pos.y, right_y);
+    }
+
+    #[test]
+    fn paddles_move_at_the_configured_speed() {
+        let mut state = playing(GameSettings::builder().paddle_speed(12.).build().unwrap());
+        let left_y = state.left_paddle.pos.y;
+        state
+            .simulate_frame(
+                &pressing(&[KeyCode::S]),
+                FRAME_TIME,
+                HEADLESS_WIDTH,
+                HEADLESS_HEIGHT,
+            )
+            .unwrap();
+
+        assert_eq!(state.left_paddle.pos.y - left_y, 12.);
+        assert_eq!(state.ai.speed, 12.);
+    }
+
+    #[test]
+    fn net_bounces_a_low_ball_and_lets_a_high_one_over() {
+        let cross_center_at = |y| {
+            let mut state = playing(GameSettings {
+                net_height: 100.,
+                ..GameSettings::default()
+            });
+            state.ball.pos = Point2 {
+                x: HEADLESS_WIDTH / 2. - 30.,
+                y,
+            };
+            state.ball.vel = Point2 { x: 5., y: 0. };
+            for _ in 0..20 {
+                state.step(HEADLESS_WIDTH, HEADLESS_HEIGHT).unwrap();
+            }
+            state.ball
+        };
+
+        let low = cross_center_at(HEADLESS_HEIGHT - 30.);
+        assert!(low.vel.x < 0.);
+        assert!(low.pos.x < HEADLESS_WIDTH / 2.);
+
+        let high = cross_center_at(HEADLESS_HEIGHT / 4.);
+        assert!(high.vel.x > 0.);
+        assert!(high.pos.x > HEADLESS_WIDTH / 2.);
+    }
+
+    use crate::walls::{CLASSIC_WALLS, WallBehavior};
+
+    /// Sends the ball from `pos` along `vel` for a few frames.
+    fn launch(state: &mut GameState, pos: Point2<f32>, vel: Point2<f32>) {
+        state.ball.pos = pos;
+        state.ball.vel = vel;
+        for _ in 0..5 {
+            state.step(HEADLESS_WIDTH, HEADLESS_HEIGHT).unwrap();
+        }
+    }
+
+    #[test]
+    fn scoring_top_and_bottom_with_wrapping_sides() {
+        let mut state = playing(GameSettings {
+            walls: [
+                WallBehavior::Score,
+                WallBehavior::Score,
+                WallBehavior::Wrap,
+                WallBehavior::Wrap,
+            ],
+            ..GameSettings::default()
+        });
+        state.server = Side::Left;
+
+        launch(
+            &mut state,
+            Point2 { x: 5., y: 50. },
+            Point2 { x: -5., y: 0. },
+        );
+        assert!(state.ball.pos.x > HEADLESS_WIDTH / 2.);
+        assert_eq!(state.score(), (0, 0));
+
+        launch(
+            &mut state,
+            Point2 {
+                x: HEADLESS_WIDTH / 2.,
+                y: 5.,
+            },
+            Point2 { x: 0., y: -5. },
+        );
+        assert_eq!(state.score(), (0, 1));
+    }
+
+    #[test]
+    fn classic_walls_bounce_off_the_top() {
+        let mut state = playing(GameSettings {
+            walls: CLASSIC_WALLS,
+            ..GameSettings::default()
+        });
+
+        launch(
+            &mut state,
+            Point2 {
+                x: HEADLESS_WIDTH / 2.,
+                y: 5.,
+            },
+            Point2 { x: 0., y: -5. },
+        );
+        assert!(state.ball.vel.y > 0.);
+        assert_eq!(state.score(), (0, 0));
+    }
+
+    #[test]
+    fn warmup_points_dont_count_until_both_players_start_the_match() {
+        let mut state = playing(GameSettings {
+            warmup: true,
+            ..GameSettings::default()
+        });
+
+        concede(&mut state, Side::Left);
+        assert_eq!(state.score(), (0, 0));
+
+        // Serve keys only count from the frame after the match started
+        state
+            .simulate_frame(
+                &InputFrame::default(),
+                FRAME_TIME,
+                HEADLESS_WIDTH,
+                HEADLESS_HEIGHT,
+            )
+            .unwrap();
+        state
+            .simulate_frame(
+                &pressing(&[KeyCode::D]),
+                FRAME_TIME,
+                HEADLESS_WIDTH,
+                HEADLESS_HEIGHT,
+            )
+            .unwrap();
+        assert!(state.warmup);
+        state
+            .simulate_frame(
+                &pressing(&[KeyCode::Return]),
+                FRAME_TIME,
+                HEADLESS_WIDTH,
+                HEADLESS_HEIGHT,
+            )
+            .unwrap();
+        assert!(!state.warmup);
+
+        concede(&mut state, Side::Left);
+        assert_eq!(state.score(), (0, 1));
+    }
+
+    #[test]
+    fn the_return_that_starts_a_match_doesnt_ready_the_warmup() {
+        let mut state = GameState::with_bounds(
+            HEADLESS_WIDTH,
+            HEADLESS_HEIGHT,
+            GameSettings {
+                warmup: true,
+                ..GameSettings::default()
+            },
+        );
+        let start = pressing(&[KeyCode::Return]);
+
+        state.handle_input(&start, HEADLESS_WIDTH, HEADLESS_HEIGHT);
+        assert_eq!(state.screen, Screen::Playing);
+        state
+            .simulate_frame(&start, FRAME_TIME, HEADLESS_WIDTH, HEADLESS_HEIGHT)
+            .unwrap();
+        assert_eq!(state.warmup_ready, (false, false));
+
+        state
+            .simulate_frame(&start, FRAME_TIME, HEADLESS_WIDTH, HEADLESS_HEIGHT)
+            .unwrap();
+        assert_eq!(state.warmup_ready, (false, true));
+    }
+
+    #[test]
+    fn entering_the_burst_zone_boosts_the_ball_once() {
+        let mut state = playing(GameSettings {
+            speed_burst: true,
+            ..GameSettings::default()
+        });
+        let zone = state.burst_zone.unwrap();
+        state.ball.pos = Point2 {
+            x: zone.left() - 20.,
+            y: zone.y + zone.h / 2.,
+        };
+        state.ball.vel = Point2 { x: 4., y: 0. };
+
+        let mut speeds = Vec::new();
+        for _ in 0..10 {
+            state.step(HEADLESS_WIDTH, HEADLESS_HEIGHT).unwrap();
+            speeds.push(state.ball.speed());
+        }
+        assert!(speeds.contains(&4.));
+        assert_eq!(speeds.last(), Some(&(4. * BURST_SPEED_FACTOR)));
+        assert!(
+            speeds
+                .iter()
+                .all(|&speed| speed == 4. || speed == 4. * BURST_SPEED_FACTOR)
+        );
+    }
+
+    #[test]
+    fn winning_a_match_adds_to_the_modes_record() {
+        let mut state = playing(
+            GameSettings::builder()
+                .elimination(true)
+                .starting_lives(1)
+                .build()
+                .unwrap(),
+        );
+        let mode = GameMode::ALL[state.menu_selection].name();
+
+        concede(&mut state, Side::Left);
+        assert_eq!(state.winner, Some(Side::Right));
+        assert_eq!(state.records.wins(mode), (0, 1));
+    }
+
+    #[test]
+    fn zero_delay_serves_again_straight_after_a_point() {
+        let mut state = playing(GameSettings {
+            serve_delay: std::time::Duration::ZERO,
+            ..GameSettings::default()
+        });
+        let none = InputFrame::default();
+        let frame = |state: &mut GameState| {
+            state
+                .simulate_frame(&none, FRAME_TIME, HEADLESS_WIDTH, HEADLESS_HEIGHT)
+                .unwrap();
+        };
+
+        state.ball.pos = Point2 {
+            x: -1.,
+            y: HEADLESS_HEIGHT / 2.,
+        };
+        state.ball.vel = Point2 { x: -5., y: 0. };
+        frame(&mut state);
+        assert_eq!(state.score(), (0, 1));
+        assert!(state.delay_duration.is_zero());
+        assert!(state.ball.is_within(HEADLESS_WIDTH, HEADLESS_HEIGHT, 0.));
+
+        let served_from = state.ball.pos;
+        frame(&mut state);
+        assert_ne!(state.ball.pos, served_from);
+        assert_eq!(state.score(), (0, 1));
+    }
+
+    #[test]
+    fn match_point_slows_the_rally_until_it_no_longer_is_one() {
+        let mut state = playing(
+            GameSettings::builder()
+                .elimination(true)
+                .starting_lives(2)
+                .build()
+                .unwrap(),
+        );
+        state.settings.match_point_slow_mo = true;
+        let play = |state: &mut GameState| {
+            state.delay_duration = std::time::Duration::ZERO;
+            state
+                .simulate_frame(
+                    &InputFrame::default(),
+                    FRAME_TIME,
+                    HEADLESS_WIDTH,
+                    HEADLESS_HEIGHT,
+                )
+                .unwrap();
+        };
+
+        play(&mut state);
+        assert_eq!(state.time_scale, 1.);
+
+        concede(&mut state, Side::Left);
+        play(&mut state);
+        assert!(state.time_scale < 1.);
+
+        state.start_match(HEADLESS_WIDTH, HEADLESS_HEIGHT);
+        play(&mut state);
+        assert_eq!(state.time_scale, 1.);
+    }
+
+    #[test]
+    fn one_point_short_of_the_winning_score_is_match_point() {
+        let mut state = playing(GameSettings {
+            winning_score: Some(5),
+            ..GameSettings::default()
+        });
+        state.score = (3, 2);
+        assert!(!state.is_match_point());
+        state.score = (3, 4);
+        assert!(state.is_match_point());
+
+        // Level at the threshold under win-by-two, nobody is a point away
+        state.settings.win_by_two = true;
+        state.score = (4, 4);
+        assert!(!state.is_match_point());
+        state.score = (5, 4);
+        assert!(state.is_match_point());
+    }
+
+    #[test]
+    fn winner_follows_each_win_condition() {
+        let mut state = playing(GameSettings::default());
+        state.score = (30, 2);
+        assert_eq!(state.winner(), None);
+
+        state.settings.elimination = true;
+        state.lives = (0, 1);
+        assert_eq!(state.winner(), Some(Side::Right));
+        state.lives = (1, 0);
+        assert_eq!(state.winner(), Some(Side::Left));
+        state.lives = (1, 1);
+        assert_eq!(state.winner(), None);
+
+        state.settings.elimination = false;
+        state.settings.time_limit = Some(std::time::Duration::from_secs(60));
+        state.match_time = std::time::Duration::from_secs(59);
+        assert_eq!(state.winner(), None);
+        state.match_time = std::time::Duration::from_secs(60);
+        assert_eq!(state.winner(), Some(Side::Left));
+        state.score = (3, 4);
+        assert_eq!(state.winner(), Some(Side::Right));
+        state.score = (4, 4);
+        assert_eq!(state.winner(), None);
+
+        // A decided match keeps its winner
+        state.winner = Some(Side::Left);
+        assert_eq!(state.winner(), Some(Side::Left));
+    }
+
+    #[test]
+    fn reaching_the_winning_score_wins_the_match() {
+        let mut state = playing(GameSettings {
+            winning_score: Some(11),
+            ..GameSettings::default()
+        });
+        state.score = (10, 9);
+        assert_eq!(state.winner(), None);
+
+        concede(&mut state, Side::Right);
+        assert_eq!(state.score, (11, 9));
+        assert_eq!(state.winner(), Some(Side::Left));
+    }
+
+    #[test]
+    fn win_by_two_plays_a_deuce_on() {
+        let mut state = playing(GameSettings {
+            winning_score: Some(11),
+            win_by_two: true,
+            ..GameSettings::default()
+        });
+        state.score = (10, 10);
+        concede(&mut state, Side::Left);
+        assert_eq!(state.score, (10, 11));
+        assert_eq!(state.winner(), None);
+
+        concede(&mut state, Side::Right);
+        concede(&mut state, Side::Right);
+        assert_eq!(state.winner(), None);
+        concede(&mut state, Side::Right);
+        assert_eq!(state.score, (13, 11));
+        assert_eq!(state.winner(), Some(Side::Left));
+    }
+
+    #[test]
+    fn no_winning_score_plays_on_without_overflowing() {
+        let mut state = playing(GameSettings::default());
+        state.score = (u8::MAX, 0);
+
+        concede(&mut state, Side::Right);
+        assert_eq!(state.score, (u8::MAX, 0));
+        assert_eq!(state.winner(), None);
+    }
+
+    #[test]
+    fn assisted_serves_start_flatter_and_slower() {
+        let mut state = playing(GameSettings {
+            beginner_assist: true,
+            ..GameSettings::default()
+        });
+        // Speed and steepest angle over a batch of serves
+        let serves = |state: &mut GameState| {
+            (0..200).fold((0f32, 0f32), |(speed, angle), _| {
+                let ball = state.center_serve(HEADLESS_WIDTH, HEADLESS_HEIGHT);
+                let serve_angle = ball.vel.y.atan2(ball.vel.x.abs()).abs();
+                (speed.max(ball.speed()), angle.max(serve_angle))
+            })
+        };
+
+        let (early_speed, early_angle) = serves(&mut state);
+        state.score = (6, 4);
+        let (late_speed, late_angle) = serves(&mut state);
+
+        let full_speed = state.settings.ball_speed;
+        assert!((early_speed - full_speed * ASSIST_START_SPEED).abs() < 1e-3);
+        assert!((late_speed - full_speed).abs() < 1e-3);
+        assert!(early_angle <= state.settings.max_serve_angle * ASSIST_START_ANGLE + 1e-4);
+        assert!(late_angle > early_angle);
+    }
+
+    #[test]
+    fn minimizing_pauses_and_restoring_resumes() {
+        let mut state = playing(GameSettings::default());
+        let ball = state.ball.pos;
+
+        state.handle_minimize(0., 0.);
+        assert!(state.paused);
+        state.handle_minimize(0., 0.);
+        assert!(state.paused);
+        assert_eq!(state.ball.pos, ball);
+
+        state.handle_minimize(HEADLESS_WIDTH, HEADLESS_HEIGHT);
+        assert!(!state.paused);
+
+        // A game paused before minimizing stays paused on restore
+        state.paused = true;
+        state.handle_minimize(0., 0.);
+        state.handle_minimize(HEADLESS_WIDTH, HEADLESS_HEIGHT);
+        assert!(state.paused);
     }
 }
@@ -0,0 +1,113 @@
+use ggez::graphics::Rect;
+
+/// Whether two rects overlap. Rects that only touch along an edge don't count.
+pub fn intersects(a: Rect, b: Rect) -> bool {
+    a.left() < b.right() && b.left() < a.right() && a.top() < b.bottom() && b.top() < a.bottom()
+}
+
+/// The canvas screen coordinates for a `width` by `height` field. Mirroring swaps the
+/// left and right edges, flipping everything drawn horizontally.
+pub fn screen_coordinates(width: f32, height: f32, mirrored: bool) -> Rect {
+    if mirrored {
+        Rect::new(width, 0., -width, height)
+    } else {
+        Rect::new(0., 0., width, height)
+    }
+}
+
+/// Positions of the lines of a grid with `spacing`, from 0 up to and including `bound`.
+/// Yields nothing for a spacing that isn't positive and finite.
+pub fn grid_lines(spacing: f32, bound: f32) -> impl Iterator<Item = f32> {
+    let count = if spacing > 0. && spacing.is_finite() && bound >= 0. {
+        (bound / spacing).floor() as usize + 1
+    } else {
+        0
+    };
+    (0..count).map(move |i| i as f32 * spacing)
+}
+
+/// `value` moved to the nearest line of a grid with `spacing`.
+pub fn snap_to_grid(value: f32, spacing: f32) -> f32 {
+    (value / spacing).round() * spacing
+}
+
+/// Fields smaller than this shrink the paddles and ball when scaling to the window is on
+pub const SMALL_BOARD_WIDTH: f32 = 640.0;
+pub const SMALL_BOARD_HEIGHT: f32 = 480.0;
+/// Sizes never shrink below this share of their full size
+pub const MIN_SIZE_SCALE: f32 = 0.25;
+
+/// How much to scale paddle and ball sizes on a `width` by `height` field:
+/// 1 down to the small board size, then proportionally smaller.
+pub fn small_board_scale(width: f32, height: f32) -> f32 {
+    let scale = (width / SMALL_BOARD_WIDTH).min(height / SMALL_BOARD_HEIGHT);
+    if scale.is_finite() {
+        scale.clamp(MIN_SIZE_SCALE, 1.)
+    } else {
+        1.
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overlapping_rects_intersect() {
+        let a = Rect::new(0., 0., 10., 10.);
+        assert!(intersects(a, Rect::new(5., 5., 10., 10.)));
+        assert!(intersects(a, Rect::new(2., 2., 2., 2.)));
+    }
+
+    #[test]
+    fn touching_rects_do_not_intersect() {
+        let a = Rect::new(0., 0., 10., 10.);
+        assert!(!intersects(a, Rect::new(10., 0., 10., 10.)));
+        assert!(!intersects(a, Rect::new(0., 10., 10., 10.)));
+    }
+
+    #[test]
+    fn disjoint_rects_do_not_intersect() {
+        let a = Rect::new(0., 0., 10., 10.);
+        assert!(!intersects(a, Rect::new(20., 20., 5., 5.)));
+        assert!(!intersects(a, Rect::new(0., 20., 10., 5.)));
+    }
+
+    #[test]
+    fn mirrored_view_flips_x_only() {
+        // Share of the way across the window that field position `x` is drawn at
+        let drawn_at = |view: Rect, x: f32| (x - view.x) / view.w;
+
+        let normal = screen_coordinates(800., 600., false);
+        assert_eq!(normal, Rect::new(0., 0., 800., 600.));
+        assert_eq!(drawn_at(normal, 200.), 0.25);
+
+        let mirrored = screen_coordinates(800., 600., true);
+        assert_eq!(drawn_at(mirrored, 200.), 0.75);
+        assert_eq!(drawn_at(mirrored, 0.), 1.);
+        assert_eq!((mirrored.y, mirrored.h), (0., 600.));
+    }
+
+    #[test]
+    fn only_small_boards_scale_sizes_down() {
+        assert_eq!(small_board_scale(1024., 768.), 1.);
+        assert_eq!(small_board_scale(SMALL_BOARD_WIDTH, SMALL_BOARD_HEIGHT), 1.);
+        // The tighter dimension decides
+        assert_eq!(small_board_scale(320., 480.), 0.5);
+        assert_eq!(small_board_scale(10., 10.), MIN_SIZE_SCALE);
+        assert_eq!(small_board_scale(0., 0.), MIN_SIZE_SCALE);
+    }
+
+    #[test]
+    fn grid_lines_run_from_zero_to_the_bound() {
+        let lines: Vec<f32> = grid_lines(100., 350.).collect();
+        assert_eq!(lines, vec![0., 100., 200., 300.]);
+        assert_eq!(grid_lines(50., 100.).last(), Some(100.));
+        assert_eq!(grid_lines(0., 100.).count(), 0);
+        assert_eq!(grid_lines(f32::NAN, 100.).count(), 0);
+        assert_eq!(grid_lines(50., -1.).count(), 0);
+
+        assert_eq!(snap_to_grid(149., 100.), 100.);
+        assert_eq!(snap_to_grid(151., 100.), 200.);
+    }
+}
@@ -0,0 +1,91 @@
+use std::time::Duration;
+
+use ggez::GameResult;
+
+use crate::events::TimedEvent;
+use crate::game::GameState;
+use crate::input::InputFrame;
+use crate::settings::GameSettings;
+use crate::side::Side;
+
+/// Field size and frame time used for headless matches
+pub const HEADLESS_WIDTH: f32 = 800.0;
+pub const HEADLESS_HEIGHT: f32 = 600.0;
+pub const FRAME_TIME: Duration = Duration::from_nanos(16_666_667);
+
+/// Headless matches give up after this many frames
+pub const MAX_FRAMES: usize = 60 * 60 * 10;
+
+/// How a headless match played out.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchResult {
+    pub score: (u8, u8),
+    pub winner: Option<Side>,
+    pub events: Vec<TimedEvent>,
+    pub frames: usize,
+    /// Every frame's input, including the idle frames played once the script ran out
+    pub inputs: Vec<InputFrame>,
+}
+
+/// Plays a match without a window, feeding `script` in one frame at a time.
+/// Once the script runs out the players stand still. The match runs until someone wins
+/// or `MAX_FRAMES` have passed. With `settings.seed` set, the result is fully reproducible.
+pub fn run_match(settings: GameSettings, script: &[InputFrame]) -> GameResult<MatchResult> {
+    let mut state = GameState::with_bounds(HEADLESS_WIDTH, HEADLESS_HEIGHT, settings);
+    state.start_match(HEADLESS_WIDTH, HEADLESS_HEIGHT);
+    let idle = InputFrame::default();
+    let mut events = Vec::new();
+    let mut inputs = Vec::new();
+
+    let mut frames = 0;
+    while state.winner().is_none() && frames < MAX_FRAMES {
+        let input = script.get(frames).unwrap_or(&idle);
+        state.simulate_frame(input, FRAME_TIME, HEADLESS_WIDTH, HEADLESS_HEIGHT)?;
+        events.extend(state.take_events());
+        inputs.push(input.clone());
+        frames += 1;
+    }
+
+    Ok(MatchResult {
+        score: state.score(),
+        winner: state.winner(),
+        events,
+        frames,
+        inputs,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::GameEvent;
+
+    fn elimination() -> GameSettings {
+        GameSettings {
+            elimination: true,
+            starting_lives: 3,
+            seed: Some(42),
+            ..GameSettings::default()
+        }
+    }
+
+    #[test]
+    fn seeded_match_plays_to_a_finish() {
+        let result = run_match(elimination(), &[]).unwrap();
+
+        let winner = result.winner.expect("the match should finish");
+        assert!(result.frames < MAX_FRAMES);
+        assert_eq!(
+            result.events.last().map(|timed| timed.event),
+            Some(GameEvent::MatchWon { winner })
+        );
+    }
+
+    #[test]
+    fn seeded_matches_are_reproducible() {
+        assert_eq!(
+            run_match(elimination(), &[]).unwrap(),
+            run_match(elimination(), &[]).unwrap()
+        );
+    }
+}
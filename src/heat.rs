@@ -0,0 +1,51 @@
+/// Heat gained per paddle hit, so a full meter takes ten hits
+pub const HEAT_PER_HIT: f32 = 0.1;
+/// What a point scored on a charged meter is worth
+pub const CHARGED_POINTS: u8 = 2;
+
+/// Combo meter that fills up during a rally. Once full, the next point counts double.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct HeatMeter {
+    /// How full the meter is, from 0 to 1
+    pub heat: f32,
+    /// The meter filled up this rally
+    pub charged: bool,
+}
+
+impl HeatMeter {
+    /// Heats the meter up for a paddle hit.
+    pub fn record_hit(&mut self) {
+        self.heat = (self.heat + HEAT_PER_HIT).min(1.);
+        if self.heat >= 1. {
+            self.charged = true;
+        }
+    }
+
+    /// Points the rally's winner gets, emptying the meter for the next rally.
+    pub fn take_points(&mut self) -> u8 {
+        let points = if self.charged { CHARGED_POINTS } else { 1 };
+        *self = HeatMeter::default();
+        points
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn full_meter_doubles_the_next_point() {
+        let mut meter = HeatMeter::default();
+        for _ in 0..9 {
+            meter.record_hit();
+        }
+        assert!(!meter.charged);
+        meter.record_hit();
+        assert!(meter.charged);
+        assert_eq!(meter.heat, 1.);
+
+        assert_eq!(meter.take_points(), CHARGED_POINTS);
+        assert_eq!(meter, HeatMeter::default());
+        assert_eq!(meter.take_points(), 1);
+    }
+}
@@ -0,0 +1,41 @@
+use crate::side::Side;
+
+/// Where the spectator HUD's panel sits and how big its text is, fixed regardless of the
+/// window size so overlay captures can rely on it
+pub const SPECTATOR_HUD_ORIGIN: (f32, f32) = (10.0, 10.0);
+pub const SPECTATOR_HUD_PADDING: f32 = 8.0;
+pub const SPECTATOR_SCORE_SCALE: f32 = 48.0;
+pub const SPECTATOR_DETAIL_SCALE: f32 = 18.0;
+
+/// The text of the spectator HUD: the score line, then one line per detail.
+pub fn spectator_lines(
+    score: (u8, u8),
+    server: Side,
+    rally_hits: u32,
+    ball_speed: f32,
+) -> (String, Vec<String>) {
+    (
+        format!("{} - {}", score.0, score.1),
+        vec![
+            format!("Serving: {}", server.name()),
+            format!("Rally: {rally_hits}"),
+            format!("Ball speed: {ball_speed:.1}"),
+        ],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spectator_lines_show_the_current_state() {
+        let (score, details) = spectator_lines((7, 4), Side::Right, 12, 8.34);
+
+        assert_eq!(score, "7 - 4");
+        assert_eq!(
+            details,
+            vec!["Serving: Right", "Rally: 12", "Ball speed: 8.3"]
+        );
+    }
+}
@@ -0,0 +1,85 @@
+use ggez::input::{
+    gamepad::{
+        GamepadContext,
+        gilrs::{Axis, Button},
+    },
+    keyboard::{KeyCode, KeyboardContext},
+};
+
+/// How far a stick has to be pushed before it moves a paddle
+const STICK_DEAD_ZONE: f32 = 0.5;
+
+/// The player input captured for a single frame.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InputFrame {
+    /// Keys held down this frame, sorted so frames compare and print consistently
+    pub pressed: Vec<KeyCode>,
+    /// Keys that went down this frame
+    pub just_pressed: Vec<KeyCode>,
+    /// Whether any controller is plugged in
+    pub controller_connected: bool,
+    /// How far the first controller is pointing up (-1) or down (1), 0 inside the stick's
+    /// dead zone. The d-pad always counts as fully pressed.
+    pub controller_axis: f32,
+}
+
+impl InputFrame {
+    pub fn capture(keyboard: &KeyboardContext, gamepads: &GamepadContext) -> InputFrame {
+        let mut pressed: Vec<KeyCode> = keyboard.pressed_keys().iter().copied().collect();
+        pressed.sort();
+        let just_pressed = pressed
+            .iter()
+            .copied()
+            .filter(|key| keyboard.is_key_just_pressed(*key))
+            .collect();
+
+        let controller = gamepads.gamepads().next().map(|(_, gamepad)| gamepad);
+        let controller_axis = controller.map_or(0., |gamepad| {
+            // Stick y points up, screen y points down
+            let stick = -gamepad.value(Axis::LeftStickY);
+            if gamepad.is_pressed(Button::DPadUp) {
+                -1.
+            } else if gamepad.is_pressed(Button::DPadDown) {
+                1.
+            } else if stick.abs() > STICK_DEAD_ZONE {
+                stick.clamp(-1., 1.)
+            } else {
+                0.
+            }
+        });
+
+        InputFrame {
+            pressed,
+            just_pressed,
+            controller_connected: controller.is_some(),
+            controller_axis,
+        }
+    }
+
+    /// Net direction from a pair of opposing keys: -1 for `negative`, 1 for `positive`,
+    /// and 0 when neither or both are held.
+    pub fn axis(&self, negative: KeyCode, positive: KeyCode) -> f32 {
+        let held = |key| if self.pressed.contains(&key) { 1. } else { 0. };
+        held(positive) - held(negative)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opposing_keys_cancel_out() {
+        let frame = |keys: &[KeyCode]| InputFrame {
+            pressed: keys.to_vec(),
+            ..InputFrame::default()
+        };
+        assert_eq!(frame(&[KeyCode::W]).axis(KeyCode::W, KeyCode::S), -1.);
+        assert_eq!(frame(&[KeyCode::S]).axis(KeyCode::W, KeyCode::S), 1.);
+        assert_eq!(
+            frame(&[KeyCode::W, KeyCode::S]).axis(KeyCode::W, KeyCode::S),
+            0.
+        );
+        assert_eq!(frame(&[]).axis(KeyCode::W, KeyCode::S), 0.);
+    }
+}
@@ -1,20 +1,183 @@
+mod ai;
+#[cfg(feature = "audio")]
+mod audio;
+mod background;
+mod ball;
+mod config;
+// Nothing samples round trips until there's a network mode to ping over
+#[allow(dead_code)]
+mod connection;
+mod console;
+mod drawable;
+mod events;
+mod frame_log;
 mod game;
+mod geometry;
+mod harness;
+mod heat;
+mod hud;
+mod input;
+mod obstacles;
+mod paddle;
+mod powerups;
+mod records;
+mod replay;
+mod rewind;
+mod screenshot;
+mod settings;
+mod side;
+mod snapshot;
+mod stats;
+mod theme;
+mod training;
+mod walls;
+
+use std::time::Duration;
 
 use ggez::{
-    ContextBuilder, GameResult,
+    ContextBuilder, GameError, GameResult,
     event::{self},
 };
 
+use crate::config::{CONFIG_PATH, Config};
+use crate::frame_log::{FRAME_LOG_ENV_VAR, FrameLogger};
+use crate::replay::Replay;
+use crate::settings::GameSettings;
+
+/// Prints warnings and info from the `log` macros to stderr
+struct StderrLogger;
+
+impl log::Log for StderrLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::Level::Info
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            eprintln!("[{}] {}", record.level(), record.args());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: StderrLogger = StderrLogger;
+
 fn main() -> GameResult {
+    if log::set_logger(&LOGGER).is_ok() {
+        log::set_max_level(log::LevelFilter::Info);
+    }
+
+    // `--simulate <seed>` plays a headless elimination match between idle players and prints the result.
+    // `--lives <n>`, `--ball-speed <speed>`, `--paddle-speed <speed>`, `--winning-score <n>` and
+    // `--serve-delay <seconds>` tweak the match. `--replay <file>` plays back a
+    // saved replay under the settings it was recorded with instead, and `--save-replay <file>`
+    // saves the match that was played as one.
+    let args: Vec<String> = std::env::args().collect();
+    let flag_value = |flag: &str| {
+        args.iter()
+            .position(|arg| arg == flag)
+            .and_then(|i| args.get(i + 1))
+    };
+    let simulate_seed = args.iter().position(|arg| arg == "--simulate").map(|i| {
+        args.get(i + 1)
+            .and_then(|seed| seed.parse().ok())
+            .unwrap_or(0)
+    });
+    let replay_path = flag_value("--replay");
+    if simulate_seed.is_some() || replay_path.is_some() {
+        let (seed, settings, script) = match replay_path {
+            Some(path) => {
+                let replay = Replay::load(path).map_err(|e| {
+                    GameError::CustomError(format!("Could not load replay {path}: {e}"))
+                })?;
+                let settings = GameSettings {
+                    seed: Some(replay.seed),
+                    ..replay.settings
+                };
+                (replay.seed, settings, replay.frames)
+            }
+            None => {
+                let seed = simulate_seed.unwrap_or(0);
+                let mut builder = GameSettings::builder().elimination(true).seed(seed);
+                if let Some(lives) = flag_value("--lives").and_then(|lives| lives.parse().ok()) {
+                    builder = builder.starting_lives(lives);
+                }
+                if let Some(speed) = flag_value("--ball-speed").and_then(|speed| speed.parse().ok())
+                {
+                    builder = builder.ball_speed(speed);
+                }
+                if let Some(speed) =
+                    flag_value("--paddle-speed").and_then(|speed| speed.parse().ok())
+                {
+                    builder = builder.paddle_speed(speed);
+                }
+                if let Some(score) =
+                    flag_value("--winning-score").and_then(|score| score.parse().ok())
+                {
+                    builder = builder.winning_score(score);
+                }
+                if let Some(delay) = flag_value("--serve-delay")
+                    .and_then(|delay| delay.parse().ok())
+                    .and_then(|secs| Duration::try_from_secs_f32(secs).ok())
+                {
+                    builder = builder.serve_delay(delay);
+                }
+                let settings = builder
+                    .build()
+                    .map_err(|e| GameError::CustomError(format!("Invalid match settings: {e}")))?;
+                (seed, settings, Vec::new())
+            }
+        };
+
+        let result = harness::run_match(settings.clone(), &script)?;
+        if let Some(path) = flag_value("--save-replay") {
+            let replay = Replay {
+                seed,
+                settings,
+                frames: result.inputs,
+            };
+            if let Err(e) = replay.save(path) {
+                log::warn!("Could not save replay to {path}: {e}");
+            }
+        }
+        println!(
+            "Seed {seed}: {:?} won {}-{} after {} frames",
+            result.winner, result.score.0, result.score.1, result.frames
+        );
+        return Ok(());
+    }
+
+    let config = Config::load(CONFIG_PATH);
+
     // Make a Context.
     let (mut ctx, event_loop) = ContextBuilder::new("Rusty Pong", "azriv")
+        .window_setup(config.display.window_setup())
         .build()
         .expect("Could not create ggez context!");
 
+    if let Some(icon) = &config.display.icon
+        && let Err(e) = ctx.gfx.set_window_icon(&ctx.fs, icon.as_str())
+    {
+        log::warn!("Could not load window icon {icon}: {e}");
+    }
+
     // Create an instance of your event handler.
     // Usually, you should provide it with the Context object to
     // use when setting your game up.
-    let state = game::GameState::new(&mut ctx)?;
+    let mut state = game::GameState::new(&mut ctx, config)?;
+
+    // `--debug` keeps a short frame history that can be stepped through while paused,
+    // and lets G show a coordinate grid
+    if args.iter().any(|arg| arg == "--debug") {
+        state.enable_rewind();
+        state.enable_debug_grid();
+    }
+
+    // Opt-in per-frame debug log
+    if let Ok(path) = std::env::var(FRAME_LOG_ENV_VAR) {
+        state.set_frame_logger(FrameLogger::create(path)?);
+    }
 
     // Run!
     event::run(ctx, event_loop, state);
@@ -0,0 +1,146 @@
+use ggez::{graphics::Rect, mint::Point2};
+use rand::Rng;
+
+use crate::ball::Ball;
+use crate::geometry::intersects;
+
+/// Size of each obstacle
+pub const OBSTACLE_WIDTH: f32 = 20.0;
+pub const OBSTACLE_HEIGHT: f32 = 60.0;
+/// Most obstacles the settings screen allows
+pub const MAX_OBSTACLES: u8 = 5;
+/// Share of the field's width, centered, that obstacles are placed in
+pub const OBSTACLE_BAND_FRACTION: f32 = 0.3;
+/// Obstacles keep at least this far clear of the serve point in the middle of the field
+pub const SERVE_CLEARANCE: f32 = 40.0;
+/// Thickness of the net along the center line
+pub const NET_WIDTH: f32 = 6.0;
+/// Size of the speed burst zone: a share of the field's width, centered, and a fixed height
+pub const BURST_ZONE_WIDTH_FRACTION: f32 = 0.5;
+pub const BURST_ZONE_HEIGHT: f32 = 80.0;
+/// What a ball entering the speed burst zone has its speed multiplied by
+pub const BURST_SPEED_FACTOR: f32 = 1.4;
+/// Random placements tried per obstacle before giving up on fitting it in
+const PLACEMENT_ATTEMPTS: usize = 50;
+
+/// Places up to `count` obstacles at random in the central band of a `width` by `height` field.
+/// They never overlap each other or the area around the serve point. If the band is too crowded
+/// for all of them, fewer are returned.
+pub fn place_obstacles(count: u8, width: f32, height: f32, rng: &mut impl Rng) -> Vec<Rect> {
+    let band_width = width * OBSTACLE_BAND_FRACTION;
+    let band_left = (width - band_width) / 2.;
+    if count == 0 || band_width < OBSTACLE_WIDTH || height < OBSTACLE_HEIGHT {
+        return Vec::new();
+    }
+
+    let serve_area = Rect::new(
+        width / 2. - SERVE_CLEARANCE,
+        height / 2. - SERVE_CLEARANCE,
+        SERVE_CLEARANCE * 2.,
+        SERVE_CLEARANCE * 2.,
+    );
+    let mut obstacles: Vec<Rect> = Vec::new();
+    for _ in 0..count {
+        for _ in 0..PLACEMENT_ATTEMPTS {
+            let candidate = Rect::new(
+                band_left + rng.random::<f32>() * (band_width - OBSTACLE_WIDTH),
+                rng.random::<f32>() * (height - OBSTACLE_HEIGHT),
+                OBSTACLE_WIDTH,
+                OBSTACLE_HEIGHT,
+            );
+            if !intersects(candidate, serve_area)
+                && obstacles
+                    .iter()
+                    .all(|placed| !intersects(candidate, *placed))
+            {
+                obstacles.push(candidate);
+                break;
+            }
+        }
+    }
+    obstacles
+}
+
+/// The net rising `net_height` from the bottom of a `width` by `height` field along its center
+/// line, or `None` without one. It's kept clear of the serve point however high it's set.
+pub fn net_rect(net_height: f32, width: f32, height: f32) -> Option<Rect> {
+    let net_height = net_height.min(height / 2. - SERVE_CLEARANCE);
+    (net_height > 0.).then(|| {
+        Rect::new(
+            (width - NET_WIDTH) / 2.,
+            height - net_height,
+            NET_WIDTH,
+            net_height,
+        )
+    })
+}
+
+/// The band across the middle of a `width` by `height` field that speeds up balls entering it.
+pub fn burst_zone_rect(width: f32, height: f32) -> Rect {
+    let zone_width = width * BURST_ZONE_WIDTH_FRACTION;
+    let zone_height = BURST_ZONE_HEIGHT.min(height);
+    Rect::new(
+        (width - zone_width) / 2.,
+        (height - zone_height) / 2.,
+        zone_width,
+        zone_height,
+    )
+}
+
+/// Bounces `ball` off `obstacle` if its box `current` has moved into it from `previous`.
+/// A ball that came in from the side reverses horizontally, one from above or below vertically,
+/// and it's put back where it was so it can't get stuck inside. Returns true if it bounced.
+pub fn bounce_off_obstacle(
+    ball: &mut Ball,
+    previous_pos: Point2<f32>,
+    previous: Rect,
+    current: Rect,
+    obstacle: Rect,
+) -> bool {
+    if !intersects(current, obstacle) {
+        return false;
+    }
+
+    let came_from_side = previous.right() <= obstacle.left() || previous.left() >= obstacle.right();
+    if came_from_side {
+        ball.vel.x = -ball.vel.x;
+    } else {
+        ball.vel.y = -ball.vel.y;
+    }
+    ball.pos = previous_pos;
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{SeedableRng, rngs::StdRng};
+
+    #[test]
+    fn seeded_placement_gives_the_requested_obstacles_apart() {
+        let place = |seed| place_obstacles(4, 800., 600., &mut StdRng::seed_from_u64(seed));
+        let obstacles = place(11);
+        assert_eq!(obstacles.len(), 4);
+        assert_eq!(obstacles, place(11));
+
+        let serve_area = Rect::new(
+            400. - SERVE_CLEARANCE,
+            300. - SERVE_CLEARANCE,
+            SERVE_CLEARANCE * 2.,
+            SERVE_CLEARANCE * 2.,
+        );
+        for (i, obstacle) in obstacles.iter().enumerate() {
+            assert!(obstacle.left() >= 800. * (1. - OBSTACLE_BAND_FRACTION) / 2.);
+            assert!(obstacle.right() <= 800. * (1. + OBSTACLE_BAND_FRACTION) / 2.);
+            assert!(!intersects(*obstacle, serve_area));
+            for other in &obstacles[i + 1..] {
+                assert!(!intersects(*obstacle, *other));
+            }
+        }
+    }
+
+    #[test]
+    fn no_obstacles_requested_places_none() {
+        assert!(place_obstacles(0, 800., 600., &mut StdRng::seed_from_u64(0)).is_empty());
+    }
+}
@@ -0,0 +1,135 @@
+use ggez::{graphics::Rect, mint::Point2};
+
+/// Constants
+pub const PADDLE_WIDTH: f32 = 20.0;
+/// A dash multiplies the paddle's speed for a few frames, then needs time to recharge
+pub const DASH_SPEED_MULTIPLIER: f32 = 3.0;
+pub const DASH_FRAMES: u32 = 8;
+pub const DASH_COOLDOWN_FRAMES: u32 = 90;
+
+/// The top or bottom wall of the field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Wall {
+    Top,
+    Bottom,
+}
+
+pub struct Paddle {
+    /// Top-left corner of the paddle
+    pub pos: Point2<f32>,
+    pub width: f32,
+    pub height: f32,
+    /// Frames left in the current dash
+    pub dash_frames: u32,
+    /// Frames until the paddle can dash again
+    pub dash_cooldown: u32,
+}
+
+impl Paddle {
+    /// Creates a paddle at the given x position, vertically centered in the field.
+    pub fn new(x: f32, width: f32, height: f32, y_bound: f32) -> Paddle {
+        Paddle {
+            pos: Point2 {
+                x,
+                y: (y_bound / 2.) - (height / 2.),
+            },
+            width,
+            height,
+            dash_frames: 0,
+            dash_cooldown: 0,
+        }
+    }
+
+    /// Starts a dash unless the last one is still recharging.
+    pub fn try_dash(&mut self) -> bool {
+        if self.dash_cooldown > 0 {
+            return false;
+        }
+        self.dash_frames = DASH_FRAMES;
+        self.dash_cooldown = DASH_COOLDOWN_FRAMES;
+        true
+    }
+
+    /// How fast the paddle moves this frame relative to its normal speed.
+    pub fn speed_multiplier(&self) -> f32 {
+        if self.dash_frames > 0 {
+            DASH_SPEED_MULTIPLIER
+        } else {
+            1.
+        }
+    }
+
+    /// Advances the dash and its cooldown by one frame.
+    pub fn tick_dash(&mut self) {
+        self.dash_frames = self.dash_frames.saturating_sub(1);
+        self.dash_cooldown = self.dash_cooldown.saturating_sub(1);
+    }
+
+    pub fn rect(&self) -> Rect {
+        Rect::new(self.pos.x, self.pos.y, self.width, self.height)
+    }
+
+    pub fn center_y(&self) -> f32 {
+        self.pos.y + (self.height / 2.)
+    }
+
+    /// The corner on the paddle's face at `surface_x` that a ball of `radius` at `ball_pos`
+    /// overlaps, if any.
+    pub fn corner_touching(
+        &self,
+        surface_x: f32,
+        ball_pos: Point2<f32>,
+        radius: f32,
+    ) -> Option<Point2<f32>> {
+        let corner = Point2 {
+            x: surface_x,
+            y: if ball_pos.y < self.center_y() {
+                self.pos.y
+            } else {
+                self.pos.y + self.height
+            },
+        };
+        let (dx, dy) = (ball_pos.x - corner.x, ball_pos.y - corner.y);
+        (dx * dx + dy * dy <= radius * radius).then_some(corner)
+    }
+
+    /// Keeps the paddle fully inside the field vertically.
+    pub fn clamp_to(&mut self, y_bound: f32) {
+        self.pos.y = self.pos.y.min(y_bound - self.height).max(0.);
+    }
+
+    /// The wall a clamped paddle is being pushed into while moving in `direction`
+    /// (negative is up), if any.
+    pub fn pressed_wall(&self, direction: f32, y_bound: f32) -> Option<Wall> {
+        if direction < 0. && self.pos.y <= 0. {
+            Some(Wall::Top)
+        } else if direction > 0. && self.pos.y >= y_bound - self.height {
+            Some(Wall::Bottom)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pushing_into_a_wall_is_detected() {
+        let mut paddle = Paddle::new(20., PADDLE_WIDTH, 100., 600.);
+        assert_eq!(paddle.pressed_wall(-1., 600.), None);
+
+        paddle.pos.y = -30.;
+        paddle.clamp_to(600.);
+        assert_eq!(paddle.pressed_wall(-1., 600.), Some(Wall::Top));
+        // Moving away from the wall, or not at all, isn't pushing into it
+        assert_eq!(paddle.pressed_wall(1., 600.), None);
+        assert_eq!(paddle.pressed_wall(0., 600.), None);
+
+        paddle.pos.y = 900.;
+        paddle.clamp_to(600.);
+        assert_eq!(paddle.pressed_wall(1., 600.), Some(Wall::Bottom));
+        assert_eq!(paddle.pressed_wall(-1., 600.), None);
+    }
+}
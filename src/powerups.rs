@@ -0,0 +1,154 @@
+use std::time::Duration;
+
+use ggez::graphics::{Color, Rect};
+use rand::Rng;
+
+/// Size of a power-up on the field
+pub const POWER_UP_SIZE: f32 = 24.0;
+/// Most power-ups waiting on the field at once; the spawner skips its turn while it's full
+pub const MAX_POWER_UPS: usize = 3;
+/// Share of the field's width, centered, that power-ups appear in
+pub const POWER_UP_BAND_FRACTION: f32 = 0.5;
+/// How much grow and shrink change a paddle's height, and the smallest a paddle can get
+pub const POWER_UP_HEIGHT_CHANGE: f32 = 30.0;
+pub const MIN_POWERED_PADDLE_HEIGHT: f32 = 40.0;
+/// What speed-up multiplies the ball's speed by
+pub const POWER_UP_SPEED_FACTOR: f32 = 1.3;
+
+/// What a power-up does to the player whose ball collects it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerUpKind {
+    /// Their paddle gets taller
+    Grow,
+    /// Their opponent's paddle gets shorter
+    Shrink,
+    /// Another ball is served
+    MultiBall,
+    /// The ball that collected it speeds up
+    SpeedUp,
+    /// Their opponent's up and down keys swap for the rest of the round
+    Invert,
+}
+
+impl PowerUpKind {
+    pub fn color(&self) -> Color {
+        match self {
+            PowerUpKind::Grow => Color::new(0.3, 1.0, 0.4, 1.0),
+            PowerUpKind::Shrink => Color::new(1.0, 0.4, 0.3, 1.0),
+            PowerUpKind::MultiBall => Color::new(0.4, 0.7, 1.0, 1.0),
+            PowerUpKind::SpeedUp => Color::new(1.0, 0.85, 0.2, 1.0),
+            PowerUpKind::Invert => Color::new(0.8, 0.4, 1.0, 1.0),
+        }
+    }
+}
+
+/// How often each kind is spawned relative to the others
+pub const POWER_UP_WEIGHTS: [(PowerUpKind, u32); 5] = [
+    (PowerUpKind::Grow, 3),
+    (PowerUpKind::Shrink, 3),
+    (PowerUpKind::MultiBall, 1),
+    (PowerUpKind::SpeedUp, 2),
+    (PowerUpKind::Invert, 1),
+];
+
+/// A power-up waiting on the field to be collected.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PowerUp {
+    pub kind: PowerUpKind,
+    pub rect: Rect,
+}
+
+/// Spawns a weighted random power-up every `interval` of play.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PowerUpSpawner {
+    pub interval: Duration,
+    pub weights: Vec<(PowerUpKind, u32)>,
+    /// Play time since the last spawn
+    elapsed: Duration,
+}
+
+impl PowerUpSpawner {
+    pub fn new(interval: Duration) -> PowerUpSpawner {
+        PowerUpSpawner {
+            interval,
+            weights: POWER_UP_WEIGHTS.to_vec(),
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    /// Advances the spawner by `delta_time` of play, returning the kind to spawn once the
+    /// interval has passed.
+    pub fn tick(&mut self, delta_time: Duration, rng: &mut impl Rng) -> Option<PowerUpKind> {
+        self.elapsed += delta_time;
+        if self.elapsed < self.interval {
+            return None;
+        }
+        self.elapsed = Duration::ZERO;
+        self.pick(rng)
+    }
+
+    /// A kind chosen at random, each as likely as its weight. `None` if every weight is 0.
+    pub fn pick(&self, rng: &mut impl Rng) -> Option<PowerUpKind> {
+        let total: u32 = self.weights.iter().map(|(_, weight)| weight).sum();
+        if total == 0 {
+            return None;
+        }
+        let mut roll = rng.random_range(0..total);
+        for (kind, weight) in &self.weights {
+            if roll < *weight {
+                return Some(*kind);
+            }
+            roll -= weight;
+        }
+        None
+    }
+}
+
+/// A `kind` power-up at a random spot in the central band of a `width` by `height` field.
+pub fn place_power_up(kind: PowerUpKind, width: f32, height: f32, rng: &mut impl Rng) -> PowerUp {
+    let band_width = width * POWER_UP_BAND_FRACTION;
+    let band_left = (width - band_width) / 2.;
+    PowerUp {
+        kind,
+        rect: Rect::new(
+            band_left + rng.random::<f32>() * (band_width - POWER_UP_SIZE).max(0.),
+            rng.random::<f32>() * (height - POWER_UP_SIZE).max(0.),
+            POWER_UP_SIZE,
+            POWER_UP_SIZE,
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{SeedableRng, rngs::StdRng};
+
+    /// The kinds spawned over `seconds` of play, one tick per second.
+    fn spawned(spawner: &mut PowerUpSpawner, seed: u64, seconds: u32) -> Vec<PowerUpKind> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        (0..seconds)
+            .filter_map(|_| spawner.tick(Duration::from_secs(1), &mut rng))
+            .collect()
+    }
+
+    #[test]
+    fn seeded_spawner_repeats_its_sequence() {
+        let first = spawned(&mut PowerUpSpawner::new(Duration::from_secs(2)), 3, 20);
+        assert_eq!(first.len(), 10);
+        assert_eq!(
+            first,
+            spawned(&mut PowerUpSpawner::new(Duration::from_secs(2)), 3, 20)
+        );
+    }
+
+    #[test]
+    fn weights_decide_what_spawns() {
+        let mut spawner = PowerUpSpawner::new(Duration::from_secs(1));
+        spawner.weights = vec![(PowerUpKind::Grow, 0), (PowerUpKind::Invert, 1)];
+        assert_eq!(spawned(&mut spawner, 3, 4), vec![PowerUpKind::Invert; 4]);
+
+        spawner.weights = vec![(PowerUpKind::Grow, 0)];
+        assert!(spawned(&mut spawner, 3, 4).is_empty());
+    }
+}
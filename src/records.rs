@@ -0,0 +1,68 @@
+use std::{collections::BTreeMap, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::side::Side;
+
+/// Matches won by each side, per game mode. Kept for the session and saved between runs.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Records {
+    /// `(left, right)` wins keyed by the mode's name
+    pub wins: BTreeMap<String, (u32, u32)>,
+}
+
+impl Records {
+    pub fn record_win(&mut self, mode: &str, winner: Side) {
+        *winner.of_mut(self.wins.entry(mode.to_string()).or_default()) += 1;
+    }
+
+    /// `(left, right)` wins in `mode`, zero for a mode nobody has finished yet.
+    pub fn wins(&self, mode: &str) -> (u32, u32) {
+        self.wins.get(mode).copied().unwrap_or_default()
+    }
+
+    /// Loads the records from `path`, starting afresh if there are none or they can't be read.
+    pub fn load(path: impl AsRef<Path>) -> Records {
+        let path = path.as_ref();
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Records::default(),
+            Err(e) => {
+                log::warn!("Could not read records from {}: {e}", path.display());
+                return Records::default();
+            }
+        };
+
+        serde_json::from_str(&contents).unwrap_or_else(|e| {
+            log::warn!(
+                "Invalid records in {}, starting afresh: {e}",
+                path.display()
+            );
+            Records::default()
+        })
+    }
+
+    /// Writes the records as JSON to `path`.
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let json = serde_json::to_string_pretty(self).map_err(std::io::Error::other)?;
+        std::fs::write(path, json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wins_are_counted_per_mode_and_side() {
+        let mut records = Records::default();
+        assert_eq!(records.wins("Classic"), (0, 0));
+
+        records.record_win("Classic", Side::Left);
+        records.record_win("Classic", Side::Left);
+        records.record_win("Classic", Side::Right);
+        records.record_win("Zen", Side::Right);
+        assert_eq!(records.wins("Classic"), (2, 1));
+        assert_eq!(records.wins("Zen"), (0, 1));
+    }
+}
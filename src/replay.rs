@@ -0,0 +1,277 @@
+use std::{fmt, path::Path};
+
+use ggez::input::keyboard::KeyCode;
+
+use crate::input::InputFrame;
+use crate::settings::GameSettings;
+
+/// Marks the start of a binary replay file
+pub const REPLAY_MAGIC: &[u8; 8] = b"PONGRPLY";
+/// Version of the binary format written by `Replay::to_bytes`
+pub const REPLAY_VERSION: u16 = 2;
+
+/// A recorded match: the seed and settings it was played with and every frame's input.
+/// Played back through the headless harness, it reproduces the match exactly.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Replay {
+    pub seed: u64,
+    pub settings: GameSettings,
+    pub frames: Vec<InputFrame>,
+}
+
+/// Why a binary replay couldn't be read or written.
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(std::io::Error),
+    /// The data doesn't start with `REPLAY_MAGIC`
+    NotAReplay,
+    /// Written by a version of the format this build doesn't know
+    UnsupportedVersion(u16),
+    /// The data ends early or refers to something that isn't there
+    Corrupt(&'static str),
+    /// The replay holds more than the format has room for
+    TooLarge(&'static str),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::Io(e) => write!(f, "{e}"),
+            ReplayError::NotAReplay => write!(f, "not a replay file"),
+            ReplayError::UnsupportedVersion(version) => write!(
+                f,
+                "replay format version {version} is not supported (expected {REPLAY_VERSION})"
+            ),
+            ReplayError::Corrupt(reason) => write!(f, "corrupt replay: {reason}"),
+            ReplayError::TooLarge(what) => write!(f, "too many {what} to save in a replay"),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl From<std::io::Error> for ReplayError {
+    fn from(e: std::io::Error) -> Self {
+        ReplayError::Io(e)
+    }
+}
+
+impl Replay {
+    /// Encodes the replay in the compact binary format.
+    ///
+    /// After the magic and version comes the seed, then the settings as length-prefixed JSON,
+    /// then a table of every key used, each stored once by name. Frames refer to keys by their
+    /// index in that table. Fails if something doesn't fit its field, rather than truncating it.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, ReplayError> {
+        let mut keys: Vec<KeyCode> = Vec::new();
+        for frame in &self.frames {
+            for key in frame.pressed.iter().chain(&frame.just_pressed) {
+                if !keys.contains(key) {
+                    keys.push(*key);
+                }
+            }
+        }
+
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(REPLAY_MAGIC);
+        bytes.extend_from_slice(&REPLAY_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&self.seed.to_le_bytes());
+
+        let settings = serde_json::to_vec(&self.settings)
+            .map_err(|e| ReplayError::Io(std::io::Error::other(e)))?;
+        let settings_len =
+            u32::try_from(settings.len()).map_err(|_| ReplayError::TooLarge("settings bytes"))?;
+        bytes.extend_from_slice(&settings_len.to_le_bytes());
+        bytes.extend_from_slice(&settings);
+
+        bytes.push(u8::try_from(keys.len()).map_err(|_| ReplayError::TooLarge("keys"))?);
+        for key in &keys {
+            let name = key_name(*key);
+            bytes.push(
+                u8::try_from(name.len()).map_err(|_| ReplayError::TooLarge("key name bytes"))?,
+            );
+            bytes.extend_from_slice(name.as_bytes());
+        }
+
+        let frame_count =
+            u32::try_from(self.frames.len()).map_err(|_| ReplayError::TooLarge("frames"))?;
+        bytes.extend_from_slice(&frame_count.to_le_bytes());
+        for frame in &self.frames {
+            for list in [&frame.pressed, &frame.just_pressed] {
+                bytes.push(
+                    u8::try_from(list.len())
+                        .map_err(|_| ReplayError::TooLarge("keys in a frame"))?,
+                );
+                for key in list {
+                    let index = keys.iter().position(|k| k == key).unwrap_or(0);
+                    bytes.push(u8::try_from(index).map_err(|_| ReplayError::TooLarge("keys"))?);
+                }
+            }
+            bytes.push(u8::from(frame.controller_connected));
+            bytes.extend_from_slice(&frame.controller_axis.to_le_bytes());
+        }
+        Ok(bytes)
+    }
+
+    /// Decodes a replay written by `to_bytes`, rejecting other data and unknown versions.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Replay, ReplayError> {
+        let mut reader = Reader { bytes };
+        if reader.take(REPLAY_MAGIC.len()).ok() != Some(REPLAY_MAGIC.as_slice()) {
+            return Err(ReplayError::NotAReplay);
+        }
+        let version = u16::from_le_bytes(reader.array()?);
+        if version != REPLAY_VERSION {
+            return Err(ReplayError::UnsupportedVersion(version));
+        }
+        let seed = u64::from_le_bytes(reader.array()?);
+
+        let settings_len = u32::from_le_bytes(reader.array()?);
+        let settings = serde_json::from_slice(reader.take(settings_len as usize)?)
+            .map_err(|_| ReplayError::Corrupt("settings are not valid"))?;
+
+        let key_count = reader.byte()?;
+        let mut keys = Vec::with_capacity(key_count as usize);
+        for _ in 0..key_count {
+            let len = reader.byte()?;
+            let name = std::str::from_utf8(reader.take(len as usize)?)
+                .map_err(|_| ReplayError::Corrupt("key name is not text"))?;
+            keys.push(key_from_name(name).ok_or(ReplayError::Corrupt("unknown key"))?);
+        }
+
+        let frame_count = u32::from_le_bytes(reader.array()?);
+        let mut frames = Vec::new();
+        for _ in 0..frame_count {
+            let mut lists = [Vec::new(), Vec::new()];
+            for list in &mut lists {
+                let len = reader.byte()?;
+                for _ in 0..len {
+                    let key = keys
+                        .get(reader.byte()? as usize)
+                        .ok_or(ReplayError::Corrupt("key index out of range"))?;
+                    list.push(*key);
+                }
+            }
+            let [pressed, just_pressed] = lists;
+            frames.push(InputFrame {
+                pressed,
+                just_pressed,
+                controller_connected: reader.byte()? != 0,
+                controller_axis: f32::from_le_bytes(reader.array()?),
+            });
+        }
+
+        Ok(Replay {
+            seed,
+            settings,
+            frames,
+        })
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ReplayError> {
+        Ok(std::fs::write(path, self.to_bytes()?)?)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Replay, ReplayError> {
+        Replay::from_bytes(&std::fs::read(path)?)
+    }
+}
+
+/// A key's name as the keyboard code it stands for, e.g. "W".
+fn key_name(key: KeyCode) -> String {
+    match serde_json::to_value(key) {
+        Ok(serde_json::Value::String(name)) => name,
+        _ => format!("{key:?}"),
+    }
+}
+
+fn key_from_name(name: &str) -> Option<KeyCode> {
+    serde_json::from_value(serde_json::Value::String(name.to_string())).ok()
+}
+
+/// Reads a replay's bytes front to back.
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], ReplayError> {
+        if self.bytes.len() < len {
+            return Err(ReplayError::Corrupt("unexpected end of data"));
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn byte(&mut self) -> Result<u8, ReplayError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], ReplayError> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Replay {
+        Replay {
+            seed: 42,
+            settings: GameSettings::builder()
+                .ball_speed(9.0)
+                .starting_lives(5)
+                .build()
+                .unwrap(),
+            frames: vec![
+                InputFrame::default(),
+                InputFrame {
+                    pressed: vec![KeyCode::W, KeyCode::Up],
+                    just_pressed: vec![KeyCode::Up],
+                    controller_connected: true,
+                    controller_axis: -0.5,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn bytes_round_trip() {
+        let replay = sample();
+        let decoded = Replay::from_bytes(&replay.to_bytes().unwrap()).unwrap();
+        assert_eq!(decoded, replay);
+    }
+
+    #[test]
+    fn unknown_version_is_rejected() {
+        let mut bytes = sample().to_bytes().unwrap();
+        let version = REPLAY_MAGIC.len();
+        bytes[version..version + 2].copy_from_slice(&(REPLAY_VERSION + 1).to_le_bytes());
+        assert!(matches!(
+            Replay::from_bytes(&bytes),
+            Err(ReplayError::UnsupportedVersion(v)) if v == REPLAY_VERSION + 1
+        ));
+        assert!(matches!(
+            Replay::from_bytes(b"not a replay"),
+            Err(ReplayError::NotAReplay)
+        ));
+    }
+
+    #[test]
+    fn oversized_replays_fail_to_encode() {
+        let mut replay = sample();
+        replay.frames[1].pressed = vec![KeyCode::W; 256];
+        assert!(matches!(
+            replay.to_bytes(),
+            Err(ReplayError::TooLarge("keys in a frame"))
+        ));
+        // Saving fails on the encoding, before anything is written
+        assert!(matches!(
+            replay.save("missing_dir/oversized.replay"),
+            Err(ReplayError::TooLarge(_))
+        ));
+    }
+}
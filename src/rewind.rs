@@ -0,0 +1,247 @@
+use std::{collections::VecDeque, ops::Range, path::Path};
+
+use serde::Serialize;
+
+use crate::snapshot::Snapshot;
+
+/// How many recent frames can be stepped back through
+pub const REWIND_FRAMES: usize = 120;
+
+/// Debug history of the last few frames, for stepping back and forth through them while paused.
+/// Only the parts of the match a `Snapshot` holds are shown, so rewinding is for looking back:
+/// play always carries on from the newest frame.
+#[derive(Debug, Clone, Default)]
+pub struct Rewind {
+    history: VecDeque<Snapshot>,
+    // Index into `history` of the frame currently shown
+    cursor: usize,
+}
+
+impl Rewind {
+    /// Records a newly simulated frame and shows it.
+    pub fn record(&mut self, snapshot: Snapshot) {
+        self.history.push_back(snapshot);
+        if self.history.len() > REWIND_FRAMES {
+            self.history.pop_front();
+        }
+        self.cursor = self.history.len() - 1;
+    }
+
+    /// Moves one frame back, returning it unless already at the oldest frame.
+    pub fn step_back(&mut self) -> Option<&Snapshot> {
+        if self.cursor == 0 {
+            return None;
+        }
+        self.cursor -= 1;
+        self.history.get(self.cursor)
+    }
+
+    /// Moves one frame forward, returning it unless already at the newest frame.
+    pub fn step_forward(&mut self) -> Option<&Snapshot> {
+        if self.cursor + 1 >= self.history.len() {
+            return None;
+        }
+        self.cursor += 1;
+        self.history.get(self.cursor)
+    }
+
+    /// Jumps back to the newest frame, returning it if an older one was being shown.
+    pub fn resume(&mut self) -> Option<&Snapshot> {
+        if self.frames_back() == 0 {
+            return None;
+        }
+        self.cursor = self.history.len() - 1;
+        self.history.get(self.cursor)
+    }
+
+    /// How many frames behind the newest recorded one the shown frame is.
+    pub fn frames_back(&self) -> usize {
+        self.history.len().saturating_sub(self.cursor + 1)
+    }
+}
+
+/// Every frame of a match, kept for scrubbing through it on the results screen.
+#[derive(Debug, Clone, Default)]
+pub struct MatchRecording {
+    frames: Vec<Snapshot>,
+    // Index into `frames` of the frame currently shown
+    cursor: usize,
+    // Where the current rally's frames start, and its paddle hits so far
+    rally_start: usize,
+    rally_hits: u32,
+    // Paddle hits of the longest rally so far and the frames it spans
+    best_rally: Option<(u32, Range<usize>)>,
+}
+
+/// A frame of an exported rally.
+#[derive(Serialize)]
+struct HighlightFrame {
+    ball_pos: [f32; 2],
+    ball_vel: [f32; 2],
+    left_paddle_y: f32,
+    right_paddle_y: f32,
+    score: (u8, u8),
+}
+
+impl MatchRecording {
+    /// Records a newly simulated frame, played `rally_hits` paddle hits into its rally, and
+    /// shows it. A point or fewer hits than last frame mean a new rally has begun.
+    pub fn record(&mut self, snapshot: Snapshot, rally_hits: u32) {
+        let new_rally = rally_hits < self.rally_hits
+            || self
+                .frames
+                .last()
+                .is_some_and(|last| last.score != snapshot.score);
+        if new_rally {
+            self.rally_start = self.frames.len();
+        }
+        self.rally_hits = rally_hits;
+        self.frames.push(snapshot);
+        self.cursor = self.frames.len() - 1;
+
+        // The current rally keeps extending its range for as long as it's the longest
+        let is_best = match &self.best_rally {
+            Some((hits, range)) => rally_hits > *hits || range.start == self.rally_start,
+            None => rally_hits > 0,
+        };
+        if is_best {
+            self.best_rally = Some((rally_hits, self.rally_start..self.frames.len()));
+        }
+    }
+
+    pub fn clear(&mut self) {
+        *self = MatchRecording::default();
+    }
+
+    /// Paddle hits in the match's longest rally and the frames it spans, if it had any hits.
+    pub fn best_rally(&self) -> Option<(u32, Range<usize>)> {
+        self.best_rally.clone()
+    }
+
+    /// Writes the frames of the longest rally to `path` as JSON, returning its paddle hits.
+    /// Writes nothing if no rally had a hit.
+    pub fn export_best_rally(&self, path: impl AsRef<Path>) -> std::io::Result<Option<u32>> {
+        let Some((hits, range)) = self.best_rally() else {
+            return Ok(None);
+        };
+        let frames: Vec<HighlightFrame> = self.frames[range]
+            .iter()
+            .map(|snapshot| HighlightFrame {
+                ball_pos: [snapshot.ball_pos.x, snapshot.ball_pos.y],
+                ball_vel: [snapshot.ball_vel.x, snapshot.ball_vel.y],
+                left_paddle_y: snapshot.left_paddle_y,
+                right_paddle_y: snapshot.right_paddle_y,
+                score: snapshot.score,
+            })
+            .collect();
+        let json = serde_json::to_string_pretty(&frames).map_err(std::io::Error::other)?;
+        std::fs::write(path, json)?;
+        Ok(Some(hits))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Jumps to the frame at `index`, stopping at the last one, and returns it.
+    pub fn seek(&mut self, index: usize) -> Option<&Snapshot> {
+        self.cursor = index.min(self.frames.len().saturating_sub(1));
+        self.frames.get(self.cursor)
+    }
+
+    /// Moves `steps` frames forward, or back if negative, and returns the frame now shown.
+    pub fn scrub(&mut self, steps: isize) -> Option<&Snapshot> {
+        self.seek(self.cursor.saturating_add_signed(steps))
+    }
+
+    /// How far through the match the shown frame is, from 0 at the start to 1 at the end.
+    pub fn progress(&self) -> f32 {
+        if self.frames.len() < 2 {
+            return 1.;
+        }
+        self.cursor as f32 / (self.frames.len() - 1) as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ggez::mint::Point2;
+
+    fn frame(x: f32) -> Snapshot {
+        Snapshot {
+            ball_pos: Point2 { x, y: 300. },
+            ball_vel: Point2 { x: 5., y: 0. },
+            left_paddle_y: 250.,
+            right_paddle_y: 250.,
+            score: (0, 0),
+        }
+    }
+
+    #[test]
+    fn stepping_back_then_forward_returns_to_the_newest_frame() {
+        let mut rewind = Rewind::default();
+        for x in 0..5 {
+            rewind.record(frame(x as f32));
+        }
+
+        assert_eq!(rewind.step_back(), Some(&frame(3.)));
+        assert_eq!(rewind.step_back(), Some(&frame(2.)));
+        assert_eq!(rewind.frames_back(), 2);
+        assert_eq!(rewind.step_forward(), Some(&frame(3.)));
+        assert_eq!(rewind.step_forward(), Some(&frame(4.)));
+        assert_eq!(rewind.step_forward(), None);
+        assert_eq!(rewind.frames_back(), 0);
+    }
+
+    #[test]
+    fn history_keeps_only_the_most_recent_frames() {
+        let mut rewind = Rewind::default();
+        for x in 0..REWIND_FRAMES + 10 {
+            rewind.record(frame(x as f32));
+        }
+        for _ in 1..REWIND_FRAMES {
+            assert!(rewind.step_back().is_some());
+        }
+        assert_eq!(rewind.step_back(), None);
+        assert_eq!(rewind.resume(), Some(&frame((REWIND_FRAMES + 9) as f32)));
+    }
+
+    #[test]
+    fn seeking_shows_the_recorded_frame_at_that_index() {
+        let mut recording = MatchRecording::default();
+        for x in 0..5 {
+            recording.record(frame(x as f32), 0);
+        }
+        assert_eq!(recording.progress(), 1.);
+
+        assert_eq!(recording.seek(2), Some(&frame(2.)));
+        assert_eq!(recording.progress(), 0.5);
+        assert_eq!(recording.scrub(-1), Some(&frame(1.)));
+        assert_eq!(recording.scrub(-10), Some(&frame(0.)));
+        assert_eq!(recording.scrub(10), Some(&frame(4.)));
+    }
+
+    #[test]
+    fn best_rally_spans_the_longest_rally() {
+        let mut recording = MatchRecording::default();
+        assert_eq!(recording.best_rally(), None);
+
+        // Rallies of 2, 4 and 1 hits over frames 0..3, 3..8 and 8..10
+        let hits = [0, 1, 2, 0, 1, 2, 3, 4, 0, 1];
+        for (x, hits) in hits.into_iter().enumerate() {
+            recording.record(frame(x as f32), hits);
+        }
+        assert_eq!(recording.best_rally(), Some((4, 3..8)));
+
+        // A point starts a new rally even if the hit count carries on
+        recording.record(
+            Snapshot {
+                score: (1, 0),
+                ..frame(10.)
+            },
+            5,
+        );
+        assert_eq!(recording.best_rally(), Some((5, 10..11)));
+    }
+}
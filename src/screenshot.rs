@@ -0,0 +1,49 @@
+use std::time::Duration;
+
+use ggez::{
+    Context, GameResult,
+    graphics::{self, ImageEncodingFormat, ImageFormat},
+};
+
+/// Name of the `sequence`th screenshot, taken `since_epoch` after the Unix epoch,
+/// e.g. `/screenshot-1700000000123-0.png`.
+/// The sequence number keeps shots taken within the same millisecond apart.
+pub fn file_name(since_epoch: Duration, sequence: u32) -> String {
+    format!("/screenshot-{}-{sequence}.png", since_epoch.as_millis())
+}
+
+/// Saves the frame that was just drawn as a PNG in the game's user data directory.
+pub fn save(ctx: &mut Context, path: &str) -> GameResult {
+    // Copy the frame into an image of a format that can be encoded to PNG
+    let frame = ctx.gfx.frame().clone();
+    let image = graphics::Image::new_canvas_image(
+        ctx,
+        ImageFormat::Rgba8UnormSrgb,
+        frame.width(),
+        frame.height(),
+        1,
+    );
+    let mut canvas = graphics::Canvas::from_image(ctx, image.clone(), None);
+    canvas.draw(&frame, graphics::DrawParam::new());
+    canvas.finish(ctx)?;
+
+    image.encode(ctx, ImageEncodingFormat::Png, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn screenshot_names_are_unique() {
+        let now = Duration::from_millis(1_700_000_000_123);
+        assert_eq!(file_name(now, 0), "/screenshot-1700000000123-0.png");
+
+        // Shots in the same millisecond are told apart by their sequence number
+        assert_ne!(file_name(now, 0), file_name(now, 1));
+        assert_ne!(
+            file_name(now, 0),
+            file_name(now + Duration::from_millis(1), 0)
+        );
+    }
+}
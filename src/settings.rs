@@ -0,0 +1,1053 @@
+use std::fmt;
+use std::time::Duration;
+
+use ggez::input::keyboard::KeyCode;
+use serde::{Deserialize, Serialize};
+
+use crate::ball::{BALL_SPEED, DEFAULT_MAX_SERVE_ANGLE, SERVE_ANGLE_LIMIT};
+use crate::side::Side;
+use crate::walls::{CLASSIC_WALLS, WallBehavior};
+
+/// Range and step of the ball speed slider on the settings screen
+pub const MIN_BALL_SPEED: f32 = 2.0;
+pub const MAX_BALL_SPEED: f32 = 15.0;
+pub const BALL_SPEED_STEP: f32 = 0.5;
+
+/// Step of the AI miss chance slider on the settings screen
+pub const AI_MISS_CHANCE_STEP: f32 = 0.1;
+
+/// Pixels a paddle moves per frame at full drive by default, and the range it can be set to
+pub const DEFAULT_PADDLE_SPEED: f32 = 5.0;
+pub const MIN_PADDLE_SPEED: f32 = 1.0;
+pub const MAX_PADDLE_SPEED: f32 = 20.0;
+
+/// Range and step of the paddle sensitivity slider on the settings screen
+pub const MIN_SENSITIVITY: f32 = 0.25;
+pub const MAX_SENSITIVITY: f32 = 3.0;
+pub const SENSITIVITY_STEP: f32 = 0.25;
+
+/// Step of the serve angle slider on the settings screen, 5 degrees
+pub const SERVE_ANGLE_STEP: f32 = std::f32::consts::PI / 36.;
+
+/// Widest gap allowed between a paddle and its wall, and the step it's adjusted by on the
+/// settings screen
+pub const MAX_PADDLE_MARGIN: f32 = 80.0;
+pub const PADDLE_MARGIN_STEP: f32 = 5.0;
+
+/// Range and step of the paddle height sliders on the settings screen
+pub const MIN_PADDLE_HEIGHT: f32 = 40.0;
+pub const MAX_PADDLE_HEIGHT: f32 = 200.0;
+pub const PADDLE_HEIGHT_STEP: f32 = 10.0;
+
+/// Highest the net can be set and the step it's adjusted by on the settings screen
+pub const MAX_NET_HEIGHT: f32 = 250.0;
+pub const NET_HEIGHT_STEP: f32 = 50.0;
+
+/// Range and step of the restitution slider on the settings screen
+pub const MIN_RESTITUTION: f32 = 0.5;
+pub const RESTITUTION_STEP: f32 = 0.05;
+
+/// Deficits that can trigger the comeback paddle or mercy ball, in the order the settings screen cycles them
+pub const COMEBACK_MARGINS: [Option<u8>; 5] = [None, Some(2), Some(3), Some(4), Some(5)];
+
+/// How long a time attack match lasts
+pub const TIME_ATTACK_DURATION: Duration = Duration::from_secs(120);
+
+/// How often power-ups can be set to appear, in the order the settings screen cycles them
+pub const POWER_UP_INTERVALS: [Option<Duration>; 4] = [
+    None,
+    Some(Duration::from_secs(5)),
+    Some(Duration::from_secs(10)),
+    Some(Duration::from_secs(20)),
+];
+
+/// Winning scores the settings screen cycles through. `None` has no score limit.
+pub const WINNING_SCORES: [Option<u8>; 4] = [None, Some(5), Some(11), Some(21)];
+
+/// Pauses after a point the settings screen cycles through. Zero serves straight away.
+pub const SERVE_DELAYS: [Duration; 4] = [
+    Duration::ZERO,
+    Duration::from_millis(500),
+    Duration::from_secs(1),
+    Duration::from_secs(2),
+];
+
+/// Dead-ball timeouts the settings screen cycles through
+pub const DEAD_BALL_TIMEOUTS: [Option<Duration>; 4] = [
+    None,
+    Some(Duration::from_secs(10)),
+    Some(Duration::from_secs(20)),
+    Some(Duration::from_secs(30)),
+];
+
+/// How much a paddle hit can change the ball's speed, as a fraction of it
+pub const HIT_SPEED_VARIATION: f32 = 0.15;
+
+/// How a paddle hit changes the ball's speed based on where it struck the paddle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HitSpeed {
+    /// The ball leaves at the speed it arrived
+    Preserve,
+    /// Center hits speed the ball up, edge hits slow it down
+    CenterFast,
+    /// Edge hits speed the ball up, center hits slow it down
+    EdgeFast,
+}
+
+impl HitSpeed {
+    /// Every mode in the order they're cycled through on the settings screen
+    pub const ALL: [HitSpeed; 3] = [HitSpeed::Preserve, HitSpeed::CenterFast, HitSpeed::EdgeFast];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            HitSpeed::Preserve => "Preserve",
+            HitSpeed::CenterFast => "Center fast",
+            HitSpeed::EdgeFast => "Edge fast",
+        }
+    }
+
+    /// Speed of a ball leaving the paddle, given its incoming `speed` and where it hit.
+    /// `offset` runs from -1 at one paddle edge through 0 at the center to 1 at the other.
+    pub fn speed_after_hit(&self, speed: f32, offset: f32, min: f32, max: f32) -> f32 {
+        // 1 for a dead-center hit, -1 for one right on the edge
+        let centrality = 1. - 2. * offset.abs().min(1.);
+        let factor = match self {
+            HitSpeed::Preserve => return speed,
+            HitSpeed::CenterFast => 1. + HIT_SPEED_VARIATION * centrality,
+            HitSpeed::EdgeFast => 1. - HIT_SPEED_VARIATION * centrality,
+        };
+        (speed * factor).clamp(min, max)
+    }
+}
+
+/// Which way the ball is served after each point.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ServeRule {
+    /// Either direction, at random
+    AlwaysRandom,
+    /// The players take turns serving
+    AlternateSides,
+    /// Towards the player who just conceded, so the scorer serves
+    ToLoser,
+    /// Towards the player who just scored, so the player who conceded serves
+    ToWinner,
+}
+
+impl ServeRule {
+    /// Every rule in the order they're cycled through on the settings screen
+    pub const ALL: [ServeRule; 4] = [
+        ServeRule::AlwaysRandom,
+        ServeRule::AlternateSides,
+        ServeRule::ToLoser,
+        ServeRule::ToWinner,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ServeRule::AlwaysRandom => "Random",
+            ServeRule::AlternateSides => "Alternate",
+            ServeRule::ToLoser => "To loser",
+            ServeRule::ToWinner => "To winner",
+        }
+    }
+
+    /// Who serves after `scorer` wins a point that `last_server` served,
+    /// or `None` to leave it to chance.
+    pub fn next_server(&self, last_server: Side, scorer: Side) -> Option<Side> {
+        match self {
+            ServeRule::AlwaysRandom => None,
+            ServeRule::AlternateSides => Some(last_server.opposite()),
+            ServeRule::ToLoser => Some(scorer),
+            ServeRule::ToWinner => Some(scorer.opposite()),
+        }
+    }
+}
+
+/// Shape and size of the board, which sets the window's dimensions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BoardPreset {
+    /// 4:3, the arcade original
+    Classic,
+    /// 16:9, for widescreen displays
+    Wide,
+}
+
+impl BoardPreset {
+    /// Every preset in the order they're cycled through on the menu
+    pub const ALL: [BoardPreset; 2] = [BoardPreset::Classic, BoardPreset::Wide];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BoardPreset::Classic => "Classic 4:3",
+            BoardPreset::Wide => "Wide 16:9",
+        }
+    }
+
+    /// Window width and height for the preset, in logical pixels.
+    pub fn window_size(&self) -> (f32, f32) {
+        match self {
+            BoardPreset::Classic => (800., 600.),
+            BoardPreset::Wide => (1024., 576.),
+        }
+    }
+}
+
+/// A way to play, picked on the main menu. Each sets up the settings it needs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameMode {
+    TwoPlayer,
+    VsAi,
+    Practice,
+    Zen,
+    MultiBall,
+    Doubles,
+    TimeAttack,
+}
+
+impl GameMode {
+    /// Every mode in the order they're listed on the menu
+    pub const ALL: [GameMode; 7] = [
+        GameMode::TwoPlayer,
+        GameMode::VsAi,
+        GameMode::Practice,
+        GameMode::Zen,
+        GameMode::MultiBall,
+        GameMode::Doubles,
+        GameMode::TimeAttack,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameMode::TwoPlayer => "Two-Player",
+            GameMode::VsAi => "Vs AI",
+            GameMode::Practice => "Practice",
+            GameMode::Zen => "Zen",
+            GameMode::MultiBall => "Multi-ball",
+            GameMode::Doubles => "Doubles",
+            GameMode::TimeAttack => "Time Attack",
+        }
+    }
+
+    /// Sets up `settings` for this mode. Flags belonging to other modes are switched off,
+    /// everything else is left as configured.
+    pub fn apply(&self, settings: &mut GameSettings) {
+        settings.opponent = match self {
+            GameMode::VsAi => Opponent::Ai,
+            _ => Opponent::Human,
+        };
+        settings.practice = *self == GameMode::Practice;
+        settings.zen = *self == GameMode::Zen;
+        settings.multi_ball = *self == GameMode::MultiBall;
+        settings.doubles = *self == GameMode::Doubles;
+        settings.time_limit = (*self == GameMode::TimeAttack).then_some(TIME_ATTACK_DURATION);
+    }
+}
+
+/// Who controls the right paddle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Opponent {
+    Human,
+    Ai,
+}
+
+/// Whether each player's keys move only their own paddle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PaddleLink {
+    Independent,
+    /// Every player's keys move both paddles together, for a co-op challenge defending both
+    /// sides at once
+    Linked,
+}
+
+impl PaddleLink {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PaddleLink::Independent => "Independent",
+            PaddleLink::Linked => "Linked",
+        }
+    }
+
+    /// The other link mode.
+    pub fn toggled(&self) -> PaddleLink {
+        match self {
+            PaddleLink::Independent => PaddleLink::Linked,
+            PaddleLink::Linked => PaddleLink::Independent,
+        }
+    }
+}
+
+/// The keys one player moves their paddle with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayerControls {
+    /// Which paddle the player controls
+    pub side: Side,
+    pub up: KeyCode,
+    pub down: KeyCode,
+    /// Launches the ball when serving on keypress
+    pub serve: KeyCode,
+    /// Gives the paddle a short burst of speed
+    pub dash: KeyCode,
+}
+
+impl PlayerControls {
+    pub fn new(
+        side: Side,
+        up: KeyCode,
+        down: KeyCode,
+        serve: KeyCode,
+        dash: KeyCode,
+    ) -> PlayerControls {
+        PlayerControls {
+            side,
+            up,
+            down,
+            serve,
+            dash,
+        }
+    }
+}
+
+/// Tunable gameplay settings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameSettings {
+    /// Distance between each paddle and its wall
+    pub paddle_margin: f32,
+    /// Speed of every new serve
+    pub ball_speed: f32,
+    /// Steepest angle from horizontal a serve can leave at, in radians. Wider makes serves
+    /// more chaotic, narrower makes them flatter. Never more than `SERVE_ANGLE_LIMIT`.
+    pub max_serve_angle: f32,
+    /// Share of its speed the ball keeps through each wall and paddle bounce.
+    /// 1 is perfectly elastic; lower values make a heavy ball that slows until re-served.
+    pub restitution: f32,
+    /// How far a paddle moves each frame at full drive, for players and the AI alike
+    pub paddle_speed: f32,
+    /// Paddle heights, independent per side for handicap play
+    pub left_paddle_height: f32,
+    pub right_paddle_height: f32,
+    /// Who plays the right paddle
+    pub opponent: Opponent,
+    /// Chance from 0 to 1 that the AI deliberately misjudges a return
+    pub ai_miss_chance: f32,
+    /// Multiplies how far a controller's stick moves the paddles
+    pub sensitivity: f32,
+    /// Apply `sensitivity` to the keyboard too, as a paddle speed multiplier
+    pub keyboard_sensitivity: bool,
+    /// Keys for every human player. The default moves the left paddle with W/S and the right
+    /// one with Up/Down.
+    pub controls: Vec<PlayerControls>,
+    pub paddle_link: PaddleLink,
+    /// Practice mode lets the ball be served manually during the post-score delay
+    pub practice: bool,
+    /// Whether paddles can be repositioned during the post-score delay
+    pub move_during_delay: bool,
+    /// A random first server is decided by an animated coin flip
+    pub coin_flip: bool,
+    /// After a point the ball waits until the server presses their serve key
+    pub serve_on_keypress: bool,
+    /// How long the ball waits after a point before it's served, unless serving on keypress
+    pub serve_delay: Duration,
+    /// Elimination mode: players lose lives when scored on instead of racing to a score
+    pub elimination: bool,
+    /// Lives each player starts with in elimination mode
+    pub starting_lives: u8,
+    /// The first player to reach this score wins the match. `None` plays on until another win
+    /// condition decides it.
+    pub winning_score: Option<u8>,
+    /// Reaching the winning score also takes a two-point lead, so a level match at the
+    /// threshold plays on until someone pulls clear
+    pub win_by_two: bool,
+    /// Zen mode: endless rallies with no score
+    pub zen: bool,
+    /// How many obstacles are placed in the middle of the field, 0 for none
+    pub obstacle_count: u8,
+    /// What each wall does to a ball that reaches it, listed top, bottom, left, right
+    pub walls: [WallBehavior; 4],
+    /// How far the net along the center line rises from the bottom wall, 0 for no net.
+    /// Balls crossing the center below its top bounce off it.
+    pub net_height: f32,
+    /// A band across the middle of the field speeds up the ball every time it enters
+    pub speed_burst: bool,
+    /// Keep the same obstacle layout for a whole match instead of a new one every round
+    pub fixed_obstacles: bool,
+    /// Two-player matches wait for both players to press their up key before the first serve
+    pub ready_check: bool,
+    /// Matches open with an untimed warmup rally that never scores, until both players press
+    /// their serve key
+    pub warmup: bool,
+    /// Time attack: the match ends once this much play has passed, won by whoever leads.
+    /// A level score plays on until the next point.
+    pub time_limit: Option<Duration>,
+    /// The ball grows with every paddle hit in a rally
+    pub ball_growth: bool,
+    /// A player this many points behind gets a bigger paddle until they score. `None` disables it.
+    pub comeback_margin: Option<u8>,
+    /// Extra paddle height granted by the comeback bonus
+    pub comeback_height_bonus: f32,
+    /// A player this many points behind is sometimes served a slow, arcing mercy ball
+    pub mercy_ball: Option<u8>,
+    /// Solo practice aid: target zones on the right wall that track shot accuracy
+    pub training_targets: bool,
+    /// Multi-ball mode: long rallies put extra balls into play
+    pub multi_ball: bool,
+    /// Doubles mode: two balls in play at once, each scored and served again on its own
+    pub doubles: bool,
+    /// Most balls that can be in play at once in multi-ball mode
+    pub max_balls: usize,
+    /// Long rallies fill a heat meter, and a full meter makes the next point worth double
+    pub heat_meter: bool,
+    /// Which way the ball is served after each point
+    pub serve_rule: ServeRule,
+    /// Fairness tweak overriding the serve rule: the first serve against the AI goes to the
+    /// human, and every later serve goes to whoever conceded the last point
+    pub fair_serve: bool,
+    /// Handicap gimmick: the field is drawn mirrored left to right. Text stays readable.
+    pub mirror_view: bool,
+    /// Shrink the paddles and ball along with the window once it gets very small
+    pub scale_to_window: bool,
+    /// Board shape, chosen on the menu
+    pub board: BoardPreset,
+    /// Fresh serves start slower and ramp up to full speed over a short grace period
+    pub serve_grace: bool,
+    /// The ball slows slightly while a point could decide the match
+    pub match_point_slow_mo: bool,
+    /// Beginner assist: early serves are flatter and slower, reaching full difficulty over the
+    /// first points of a match
+    pub beginner_assist: bool,
+    /// A power-up appears on the field this often during play
+    pub power_up_interval: Option<Duration>,
+    /// A rally where no paddle touches the ball for this long is served again
+    pub dead_ball_timeout: Option<Duration>,
+    /// How the hit position on a paddle affects the ball's speed
+    pub hit_speed: HitSpeed,
+    /// Bounds on the ball speed that `hit_speed` can produce
+    pub min_hit_speed: f32,
+    pub max_hit_speed: f32,
+    /// Seed for the match's random numbers. `None` picks a fresh one each run.
+    pub seed: Option<u64>,
+}
+
+impl Default for GameSettings {
+    fn default() -> Self {
+        GameSettings {
+            paddle_margin: 20.0,
+            ball_speed: BALL_SPEED,
+            max_serve_angle: DEFAULT_MAX_SERVE_ANGLE,
+            restitution: 1.0,
+            paddle_speed: DEFAULT_PADDLE_SPEED,
+            left_paddle_height: 100.0,
+            right_paddle_height: 100.0,
+            opponent: Opponent::Human,
+            ai_miss_chance: 0.1,
+            sensitivity: 1.0,
+            keyboard_sensitivity: false,
+            controls: vec![
+                PlayerControls::new(
+                    Side::Left,
+                    KeyCode::W,
+                    KeyCode::S,
+                    KeyCode::D,
+                    KeyCode::LShift,
+                ),
+                PlayerControls::new(
+                    Side::Right,
+                    KeyCode::Up,
+                    KeyCode::Down,
+                    KeyCode::Return,
+                    KeyCode::RShift,
+                ),
+            ],
+            paddle_link: PaddleLink::Independent,
+            practice: false,
+            move_during_delay: false,
+            coin_flip: false,
+            serve_on_keypress: false,
+            serve_delay: Duration::from_secs(1),
+            elimination: false,
+            starting_lives: 3,
+            winning_score: None,
+            win_by_two: false,
+            zen: false,
+            time_limit: None,
+            ready_check: false,
+            warmup: false,
+            obstacle_count: 0,
+            net_height: 0.0,
+            speed_burst: false,
+            walls: CLASSIC_WALLS,
+            fixed_obstacles: false,
+            ball_growth: false,
+            comeback_margin: None,
+            comeback_height_bonus: 40.0,
+            mercy_ball: None,
+            training_targets: false,
+            multi_ball: false,
+            doubles: false,
+            max_balls: 3,
+            serve_rule: ServeRule::AlwaysRandom,
+            fair_serve: false,
+            mirror_view: false,
+            scale_to_window: false,
+            board: BoardPreset::Classic,
+            serve_grace: false,
+            match_point_slow_mo: false,
+            beginner_assist: false,
+            dead_ball_timeout: None,
+            power_up_interval: None,
+            heat_meter: false,
+            hit_speed: HitSpeed::Preserve,
+            min_hit_speed: MIN_BALL_SPEED,
+            max_hit_speed: MAX_BALL_SPEED,
+            seed: None,
+        }
+    }
+}
+
+impl GameSettings {
+    /// A builder starting from the default settings.
+    pub fn builder() -> GameSettingsBuilder {
+        GameSettingsBuilder::default()
+    }
+
+    /// Checks the settings describe a playable match.
+    pub fn validate(&self) -> Result<(), SettingsError> {
+        if !(MIN_BALL_SPEED..=MAX_BALL_SPEED).contains(&self.ball_speed) {
+            return Err(SettingsError::BallSpeedOutOfRange(self.ball_speed));
+        }
+        if !(MIN_RESTITUTION..=1.).contains(&self.restitution) {
+            return Err(SettingsError::RestitutionOutOfRange(self.restitution));
+        }
+        if !(0. ..=SERVE_ANGLE_LIMIT).contains(&self.max_serve_angle) {
+            return Err(SettingsError::ServeAngleOutOfRange(self.max_serve_angle));
+        }
+        if !(MIN_PADDLE_SPEED..=MAX_PADDLE_SPEED).contains(&self.paddle_speed) {
+            return Err(SettingsError::PaddleSpeedOutOfRange(self.paddle_speed));
+        }
+        if !(MIN_SENSITIVITY..=MAX_SENSITIVITY).contains(&self.sensitivity) {
+            return Err(SettingsError::SensitivityOutOfRange(self.sensitivity));
+        }
+        if !(0. ..=MAX_NET_HEIGHT).contains(&self.net_height) {
+            return Err(SettingsError::NetHeightOutOfRange(self.net_height));
+        }
+        if !(MIN_BALL_SPEED <= self.min_hit_speed
+            && self.min_hit_speed <= self.max_hit_speed
+            && self.max_hit_speed <= MAX_BALL_SPEED)
+        {
+            return Err(SettingsError::HitSpeedRangeInvalid(
+                self.min_hit_speed,
+                self.max_hit_speed,
+            ));
+        }
+        if !(0. ..=1.).contains(&self.ai_miss_chance) {
+            return Err(SettingsError::MissChanceOutOfRange(self.ai_miss_chance));
+        }
+        if self.elimination && self.starting_lives == 0 {
+            return Err(SettingsError::NoStartingLives);
+        }
+        if !(0. ..=MAX_PADDLE_MARGIN).contains(&self.paddle_margin) {
+            return Err(SettingsError::PaddleMarginOutOfRange(self.paddle_margin));
+        }
+        for height in [self.left_paddle_height, self.right_paddle_height] {
+            if !(MIN_PADDLE_HEIGHT..=MAX_PADDLE_HEIGHT).contains(&height) {
+                return Err(SettingsError::PaddleHeightOutOfRange(height));
+            }
+        }
+        Ok(())
+    }
+
+    /// Nudges the serve speed by `steps` slider increments, staying within the allowed range.
+    pub fn adjust_ball_speed(&mut self, steps: f32) {
+        self.ball_speed =
+            (self.ball_speed + steps * BALL_SPEED_STEP).clamp(MIN_BALL_SPEED, MAX_BALL_SPEED);
+    }
+
+    /// Nudges the paddle sensitivity by `steps` notches, staying within the allowed range.
+    pub fn adjust_sensitivity(&mut self, steps: f32) {
+        self.sensitivity =
+            (self.sensitivity + steps * SENSITIVITY_STEP).clamp(MIN_SENSITIVITY, MAX_SENSITIVITY);
+    }
+
+    /// Moves the paddles `steps` notches further from their walls, up to `MAX_PADDLE_MARGIN`.
+    pub fn adjust_paddle_margin(&mut self, steps: f32) {
+        self.paddle_margin =
+            (self.paddle_margin + steps * PADDLE_MARGIN_STEP).clamp(0., MAX_PADDLE_MARGIN);
+    }
+
+    /// The configured height of `side`'s paddle, before any bonus.
+    pub fn paddle_height(&self, side: Side) -> f32 {
+        match side {
+            Side::Left => self.left_paddle_height,
+            Side::Right => self.right_paddle_height,
+        }
+    }
+
+    /// Grows or shrinks `side`'s paddle by `steps` notches, within the allowed range.
+    pub fn adjust_paddle_height(&mut self, side: Side, steps: f32) {
+        let height = match side {
+            Side::Left => &mut self.left_paddle_height,
+            Side::Right => &mut self.right_paddle_height,
+        };
+        *height =
+            (*height + steps * PADDLE_HEIGHT_STEP).clamp(MIN_PADDLE_HEIGHT, MAX_PADDLE_HEIGHT);
+    }
+
+    /// How far a paddle is driven this frame, as a share of its speed, given the keyboard's
+    /// and a controller's input from -1 to 1 each.
+    pub fn paddle_drive(&self, keys: f32, analog: f32) -> f32 {
+        let keys = if self.keyboard_sensitivity {
+            keys * self.sensitivity
+        } else {
+            keys
+        };
+        (keys + analog * self.sensitivity)
+            .clamp(-self.sensitivity.max(1.), self.sensitivity.max(1.))
+    }
+
+    /// Widens or narrows the serve angle by `steps` notches, between flat and `SERVE_ANGLE_LIMIT`.
+    pub fn adjust_max_serve_angle(&mut self, steps: f32) {
+        self.max_serve_angle =
+            (self.max_serve_angle + steps * SERVE_ANGLE_STEP).clamp(0., SERVE_ANGLE_LIMIT);
+    }
+
+    /// Raises or lowers the net by `steps` notches, between none and `MAX_NET_HEIGHT`.
+    pub fn adjust_net_height(&mut self, steps: f32) {
+        self.net_height = (self.net_height + steps * NET_HEIGHT_STEP).clamp(0., MAX_NET_HEIGHT);
+    }
+
+    /// Nudges the restitution by `steps` notches, staying between `MIN_RESTITUTION` and 1.
+    pub fn adjust_restitution(&mut self, steps: f32) {
+        self.restitution = (self.restitution + steps * RESTITUTION_STEP).clamp(MIN_RESTITUTION, 1.);
+    }
+
+    /// The ball's speed coming out of a bounce it went into at `speed`.
+    /// Energy loss never takes it below the slowest serve speed.
+    pub fn speed_after_bounce(&self, speed: f32) -> f32 {
+        (speed * self.restitution).max(MIN_BALL_SPEED.min(speed))
+    }
+
+    /// Nudges the AI's miss chance by `steps` tenths, staying within 0 to 1.
+    pub fn adjust_ai_miss_chance(&mut self, steps: f32) {
+        self.ai_miss_chance = (self.ai_miss_chance + steps * AI_MISS_CHANCE_STEP).clamp(0., 1.);
+    }
+
+    /// Moves the serve delay `steps` places along `SERVE_DELAYS`, wrapping around.
+    pub fn cycle_serve_delay(&mut self, steps: i32) {
+        self.serve_delay = cycled(&SERVE_DELAYS, self.serve_delay, steps);
+    }
+
+    /// Moves the winning score `steps` places along `WINNING_SCORES`, wrapping around.
+    pub fn cycle_winning_score(&mut self, steps: i32) {
+        self.winning_score = cycled(&WINNING_SCORES, self.winning_score, steps);
+    }
+
+    /// Moves the dead-ball timeout `steps` places along `DEAD_BALL_TIMEOUTS`, wrapping around.
+    pub fn cycle_dead_ball_timeout(&mut self, steps: i32) {
+        self.dead_ball_timeout = cycled(&DEAD_BALL_TIMEOUTS, self.dead_ball_timeout, steps);
+    }
+
+    /// Moves the power-up interval `steps` places along `POWER_UP_INTERVALS`, wrapping around.
+    pub fn cycle_power_up_interval(&mut self, steps: i32) {
+        self.power_up_interval = cycled(&POWER_UP_INTERVALS, self.power_up_interval, steps);
+    }
+
+    /// Moves the mercy ball deficit `steps` places along `COMEBACK_MARGINS`, wrapping around.
+    pub fn cycle_mercy_ball(&mut self, steps: i32) {
+        self.mercy_ball = cycled(&COMEBACK_MARGINS, self.mercy_ball, steps);
+    }
+
+    /// Moves the comeback margin `steps` places along `COMEBACK_MARGINS`, wrapping around.
+    pub fn cycle_comeback_margin(&mut self, steps: i32) {
+        self.comeback_margin = cycled(&COMEBACK_MARGINS, self.comeback_margin, steps);
+    }
+}
+
+/// The option `steps` places after `current` in `options`, wrapping around.
+/// Anything not in the list counts as the first option.
+pub fn cycled<T: Copy + PartialEq>(options: &[T], current: T, steps: i32) -> T {
+    let len = options.len() as i32;
+    let index = options
+        .iter()
+        .position(|option| *option == current)
+        .unwrap_or(0) as i32;
+    options[(index + steps).rem_euclid(len) as usize]
+}
+
+/// Why a set of `GameSettings` was rejected.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SettingsError {
+    /// Serve speed outside `MIN_BALL_SPEED..=MAX_BALL_SPEED`
+    BallSpeedOutOfRange(f32),
+    /// Restitution outside `MIN_RESTITUTION..=1`
+    RestitutionOutOfRange(f32),
+    /// Serve angle outside `0..=SERVE_ANGLE_LIMIT`
+    ServeAngleOutOfRange(f32),
+    /// Paddle speed outside `MIN_PADDLE_SPEED..=MAX_PADDLE_SPEED`
+    PaddleSpeedOutOfRange(f32),
+    /// Stick sensitivity outside `MIN_SENSITIVITY..=MAX_SENSITIVITY`
+    SensitivityOutOfRange(f32),
+    /// Net height outside `0..=MAX_NET_HEIGHT`
+    NetHeightOutOfRange(f32),
+    /// Hit speed bounds that are backwards or outside `MIN_BALL_SPEED..=MAX_BALL_SPEED`
+    HitSpeedRangeInvalid(f32, f32),
+    /// AI miss chance that isn't a probability
+    MissChanceOutOfRange(f32),
+    /// An elimination match where nobody has a life to lose
+    NoStartingLives,
+    /// Paddle margin outside `0..=MAX_PADDLE_MARGIN`
+    PaddleMarginOutOfRange(f32),
+    /// Paddle height outside `MIN_PADDLE_HEIGHT..=MAX_PADDLE_HEIGHT`
+    PaddleHeightOutOfRange(f32),
+}
+
+impl fmt::Display for SettingsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SettingsError::BallSpeedOutOfRange(speed) => write!(
+                f,
+                "ball speed {speed} is outside {MIN_BALL_SPEED} to {MAX_BALL_SPEED}"
+            ),
+            SettingsError::RestitutionOutOfRange(restitution) => write!(
+                f,
+                "restitution {restitution} is outside {MIN_RESTITUTION} to 1"
+            ),
+            SettingsError::ServeAngleOutOfRange(angle) => write!(
+                f,
+                "serve angle {angle} is outside 0 to {SERVE_ANGLE_LIMIT} radians"
+            ),
+            SettingsError::PaddleSpeedOutOfRange(speed) => write!(
+                f,
+                "paddle speed {speed} is outside {MIN_PADDLE_SPEED} to {MAX_PADDLE_SPEED}"
+            ),
+            SettingsError::SensitivityOutOfRange(sensitivity) => write!(
+                f,
+                "sensitivity {sensitivity} is outside {MIN_SENSITIVITY} to {MAX_SENSITIVITY}"
+            ),
+            SettingsError::NetHeightOutOfRange(height) => {
+                write!(f, "net height {height} is outside 0 to {MAX_NET_HEIGHT}")
+            }
+            SettingsError::HitSpeedRangeInvalid(min, max) => write!(
+                f,
+                "hit speeds {min} to {max} are not within {MIN_BALL_SPEED} to {MAX_BALL_SPEED}"
+            ),
+            SettingsError::MissChanceOutOfRange(chance) => {
+                write!(f, "AI miss chance {chance} is outside 0 to 1")
+            }
+            SettingsError::NoStartingLives => write!(f, "elimination needs at least one life"),
+            SettingsError::PaddleMarginOutOfRange(margin) => {
+                write!(
+                    f,
+                    "paddle margin {margin} is outside 0 to {MAX_PADDLE_MARGIN}"
+                )
+            }
+            SettingsError::PaddleHeightOutOfRange(height) => write!(
+                f,
+                "paddle height {height} is outside {MIN_PADDLE_HEIGHT} to {MAX_PADDLE_HEIGHT}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SettingsError {}
+
+/// Chainable construction of `GameSettings`, checked when built.
+#[derive(Debug, Clone, Default)]
+pub struct GameSettingsBuilder {
+    settings: GameSettings,
+}
+
+/// A builder starting from existing settings, for changing a few of them.
+impl From<GameSettings> for GameSettingsBuilder {
+    fn from(settings: GameSettings) -> Self {
+        GameSettingsBuilder { settings }
+    }
+}
+
+impl GameSettingsBuilder {
+    pub fn ball_speed(mut self, speed: f32) -> Self {
+        self.settings.ball_speed = speed;
+        self
+    }
+
+    pub fn paddle_speed(mut self, speed: f32) -> Self {
+        self.settings.paddle_speed = speed;
+        self
+    }
+
+    pub fn elimination(mut self, enabled: bool) -> Self {
+        self.settings.elimination = enabled;
+        self
+    }
+
+    pub fn starting_lives(mut self, lives: u8) -> Self {
+        self.settings.starting_lives = lives;
+        self
+    }
+
+    pub fn winning_score(mut self, score: u8) -> Self {
+        self.settings.winning_score = Some(score);
+        self
+    }
+
+    pub fn serve_delay(mut self, delay: Duration) -> Self {
+        self.settings.serve_delay = delay;
+        self
+    }
+
+    pub fn paddle_margin(mut self, margin: f32) -> Self {
+        self.settings.paddle_margin = margin;
+        self
+    }
+
+    pub fn left_paddle_height(mut self, height: f32) -> Self {
+        self.settings.left_paddle_height = height;
+        self
+    }
+
+    pub fn right_paddle_height(mut self, height: f32) -> Self {
+        self.settings.right_paddle_height = height;
+        self
+    }
+
+    pub fn move_during_delay(mut self, enabled: bool) -> Self {
+        self.settings.move_during_delay = enabled;
+        self
+    }
+
+    pub fn ball_growth(mut self, enabled: bool) -> Self {
+        self.settings.ball_growth = enabled;
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.settings.seed = Some(seed);
+        self
+    }
+
+    pub fn build(self) -> Result<GameSettings, SettingsError> {
+        self.settings.validate()?;
+        Ok(self.settings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ball::Ball;
+    use rand::{SeedableRng, rngs::StdRng};
+
+    #[test]
+    fn ball_speed_slider_sets_the_next_serve_speed() {
+        let mut settings = GameSettings::default();
+        let mut rng = StdRng::seed_from_u64(0);
+
+        settings.adjust_ball_speed(2.);
+        assert_eq!(settings.ball_speed, BALL_SPEED + 2. * BALL_SPEED_STEP);
+        let vel = Ball::random_velocity(settings.ball_speed, settings.max_serve_angle, &mut rng);
+        let speed = (vel.x * vel.x + vel.y * vel.y).sqrt();
+        assert!((speed - settings.ball_speed).abs() < 1e-4);
+
+        settings.adjust_ball_speed(100.);
+        assert_eq!(settings.ball_speed, MAX_BALL_SPEED);
+    }
+
+    #[test]
+    fn hit_position_sets_the_return_speed() {
+        let (min, max) = (1., 100.);
+        let center = HitSpeed::CenterFast.speed_after_hit(10., 0., min, max);
+        let edge = HitSpeed::CenterFast.speed_after_hit(10., 1., min, max);
+        assert!(center > 10. && edge < 10.);
+
+        let center = HitSpeed::EdgeFast.speed_after_hit(10., 0., min, max);
+        let edge = HitSpeed::EdgeFast.speed_after_hit(10., -1., min, max);
+        assert!(center < 10. && edge > 10.);
+
+        assert_eq!(HitSpeed::Preserve.speed_after_hit(10., 1., min, max), 10.);
+        assert_eq!(
+            HitSpeed::CenterFast.speed_after_hit(10., 0., min, 10.5),
+            10.5
+        );
+    }
+
+    #[test]
+    fn board_presets_have_their_aspect_ratios() {
+        assert_eq!(BoardPreset::Classic.window_size(), (800., 600.));
+        let (width, height) = BoardPreset::Wide.window_size();
+        assert!((width / height - 16. / 9.).abs() < 1e-4);
+    }
+
+    #[test]
+    fn serve_rules_pick_the_next_server() {
+        use Side::{Left, Right};
+        assert_eq!(ServeRule::AlwaysRandom.next_server(Left, Right), None);
+        assert_eq!(
+            ServeRule::AlternateSides.next_server(Left, Left),
+            Some(Right)
+        );
+        assert_eq!(
+            ServeRule::AlternateSides.next_server(Right, Left),
+            Some(Left)
+        );
+        assert_eq!(ServeRule::ToLoser.next_server(Left, Right), Some(Right));
+        assert_eq!(ServeRule::ToWinner.next_server(Left, Right), Some(Left));
+    }
+
+    #[test]
+    fn bounce_speed_never_drops_below_the_slowest_serve() {
+        let settings = GameSettings {
+            restitution: MIN_RESTITUTION,
+            ..GameSettings::default()
+        };
+        assert!(settings.speed_after_bounce(10.) < 10.);
+        assert_eq!(settings.speed_after_bounce(MIN_BALL_SPEED), MIN_BALL_SPEED);
+        assert_eq!(GameSettings::default().speed_after_bounce(10.), 10.);
+    }
+
+    #[test]
+    fn builder_builds_the_requested_settings() {
+        let settings = GameSettings::builder()
+            .ball_speed(8.)
+            .elimination(true)
+            .starting_lives(5)
+            .seed(3)
+            .left_paddle_height(MIN_PADDLE_HEIGHT)
+            .paddle_speed(8.)
+            .winning_score(7)
+            .serve_delay(Duration::ZERO)
+            .build()
+            .unwrap();
+
+        assert_eq!(settings.ball_speed, 8.);
+        assert!(settings.elimination);
+        assert_eq!(settings.starting_lives, 5);
+        assert_eq!(settings.seed, Some(3));
+        assert_eq!(settings.paddle_height(Side::Left), MIN_PADDLE_HEIGHT);
+        assert_eq!(settings.paddle_speed, 8.);
+        assert_eq!(settings.winning_score, Some(7));
+        assert_eq!(settings.serve_delay, Duration::ZERO);
+        assert_eq!(
+            GameSettings::builder().build().unwrap(),
+            GameSettings::default()
+        );
+    }
+
+    #[test]
+    fn builder_rejects_invalid_settings() {
+        assert_eq!(
+            GameSettings::builder()
+                .ball_speed(MAX_BALL_SPEED + 1.)
+                .build(),
+            Err(SettingsError::BallSpeedOutOfRange(MAX_BALL_SPEED + 1.))
+        );
+        assert_eq!(
+            GameSettings::builder()
+                .elimination(true)
+                .starting_lives(0)
+                .build(),
+            Err(SettingsError::NoStartingLives)
+        );
+        assert_eq!(
+            GameSettings::builder().paddle_margin(-1.).build(),
+            Err(SettingsError::PaddleMarginOutOfRange(-1.))
+        );
+        assert_eq!(
+            GameSettings::builder()
+                .right_paddle_height(MAX_PADDLE_HEIGHT * 2.)
+                .build(),
+            Err(SettingsError::PaddleHeightOutOfRange(
+                MAX_PADDLE_HEIGHT * 2.
+            ))
+        );
+        assert_eq!(
+            GameSettings::builder().paddle_speed(0.).build(),
+            Err(SettingsError::PaddleSpeedOutOfRange(0.))
+        );
+    }
+
+    #[test]
+    fn validation_rejects_out_of_range_tuning() {
+        let invalid = |settings: GameSettings| GameSettingsBuilder::from(settings).build();
+        assert_eq!(
+            invalid(GameSettings {
+                max_serve_angle: SERVE_ANGLE_LIMIT + 0.1,
+                ..GameSettings::default()
+            }),
+            Err(SettingsError::ServeAngleOutOfRange(SERVE_ANGLE_LIMIT + 0.1))
+        );
+        assert_eq!(
+            invalid(GameSettings {
+                sensitivity: MAX_SENSITIVITY + 1.,
+                ..GameSettings::default()
+            }),
+            Err(SettingsError::SensitivityOutOfRange(MAX_SENSITIVITY + 1.))
+        );
+        assert_eq!(
+            invalid(GameSettings {
+                net_height: -1.,
+                ..GameSettings::default()
+            }),
+            Err(SettingsError::NetHeightOutOfRange(-1.))
+        );
+        assert_eq!(
+            invalid(GameSettings {
+                min_hit_speed: 10.,
+                max_hit_speed: 5.,
+                ..GameSettings::default()
+            }),
+            Err(SettingsError::HitSpeedRangeInvalid(10., 5.))
+        );
+        assert_eq!(
+            invalid(GameSettings {
+                max_hit_speed: MAX_BALL_SPEED + 1.,
+                ..GameSettings::default()
+            }),
+            Err(SettingsError::HitSpeedRangeInvalid(
+                MIN_BALL_SPEED,
+                MAX_BALL_SPEED + 1.
+            ))
+        );
+    }
+
+    #[test]
+    fn game_modes_set_up_their_settings() {
+        let mut settings = GameSettings {
+            ball_speed: 9.,
+            ..GameSettings::default()
+        };
+        GameMode::VsAi.apply(&mut settings);
+        assert_eq!(settings.opponent, Opponent::Ai);
+
+        GameMode::TimeAttack.apply(&mut settings);
+        assert_eq!(settings.opponent, Opponent::Human);
+        assert_eq!(settings.time_limit, Some(TIME_ATTACK_DURATION));
+
+        // Switching modes clears the last one's flags and keeps everything else
+        GameMode::Zen.apply(&mut settings);
+        assert!(settings.zen);
+        assert_eq!(settings.time_limit, None);
+        assert_eq!(settings.ball_speed, 9.);
+
+        GameMode::TwoPlayer.apply(&mut settings);
+        assert_eq!(
+            settings,
+            GameSettings {
+                ball_speed: 9.,
+                ..GameSettings::default()
+            }
+        );
+    }
+
+    #[test]
+    fn sensitivity_scales_the_keyboard_only_when_asked() {
+        let mut settings = GameSettings {
+            sensitivity: 2.,
+            ..GameSettings::default()
+        };
+        assert_eq!(settings.paddle_drive(0., 0.25), 0.5);
+        assert_eq!(settings.paddle_drive(1., 0.), 1.);
+
+        settings.keyboard_sensitivity = true;
+        assert_eq!(settings.paddle_drive(1., 0.), 2.);
+        // Keys and stick together never go past the sensitivity
+        assert_eq!(settings.paddle_drive(1., 1.), 2.);
+    }
+}
@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+
+/// One half of the play field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+impl Side {
+    pub fn opposite(self) -> Side {
+        match self {
+            Side::Left => Side::Right,
+            Side::Right => Side::Left,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Side::Left => "Left",
+            Side::Right => "Right",
+        }
+    }
+
+    /// This side's entry in a `(left, right)` pair such as the score.
+    pub fn of<T>(self, pair: &(T, T)) -> &T {
+        match self {
+            Side::Left => &pair.0,
+            Side::Right => &pair.1,
+        }
+    }
+
+    pub fn of_mut<T>(self, pair: &mut (T, T)) -> &mut T {
+        match self {
+            Side::Left => &mut pair.0,
+            Side::Right => &mut pair.1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opposite_swaps_sides() {
+        assert_eq!(Side::Left.opposite(), Side::Right);
+        assert_eq!(Side::Right.opposite(), Side::Left);
+    }
+
+    #[test]
+    fn of_picks_the_sides_entry() {
+        let mut score = (3, 5);
+        assert_eq!(*Side::Left.of(&score), 3);
+        assert_eq!(*Side::Right.of(&score), 5);
+
+        *Side::Right.of_mut(&mut score) += 1;
+        assert_eq!(score, (3, 6));
+    }
+}
@@ -0,0 +1,91 @@
+use ggez::mint::Point2;
+
+/// Positions are quantized to this fraction of a pixel before hashing,
+/// so float noise below it doesn't register as a divergence.
+const HASH_QUANTUM: f32 = 0.01;
+
+/// The dynamic state of a match at a point in time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    pub ball_pos: Point2<f32>,
+    pub ball_vel: Point2<f32>,
+    pub left_paddle_y: f32,
+    pub right_paddle_y: f32,
+    pub score: (u8, u8),
+}
+
+impl Snapshot {
+    /// A hash of the snapshot that is stable across runs, platforms and compiler versions.
+    pub fn stable_hash(&self) -> u64 {
+        let quantize = |value: f32| ((value / HASH_QUANTUM).round() as i64).to_le_bytes();
+
+        let mut hasher = Fnv1a::default();
+        for value in [
+            self.ball_pos.x,
+            self.ball_pos.y,
+            self.ball_vel.x,
+            self.ball_vel.y,
+            self.left_paddle_y,
+            self.right_paddle_y,
+        ] {
+            hasher.write(&quantize(value));
+        }
+        hasher.write(&[self.score.0, self.score.1]);
+
+        hasher.0
+    }
+}
+
+/// 64-bit FNV-1a, used because `std`'s hashers aren't guaranteed stable between releases.
+struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Fnv1a(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Fnv1a {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot() -> Snapshot {
+        Snapshot {
+            ball_pos: Point2 { x: 400., y: 300. },
+            ball_vel: Point2 { x: 4., y: -2. },
+            left_paddle_y: 250.,
+            right_paddle_y: 310.,
+            score: (3, 4),
+        }
+    }
+
+    #[test]
+    fn identical_snapshots_hash_the_same() {
+        assert_eq!(snapshot().stable_hash(), snapshot().stable_hash());
+
+        // Noise below the quantum doesn't count as a divergence
+        let mut noisy = snapshot();
+        noisy.ball_pos.x += HASH_QUANTUM / 10.;
+        assert_eq!(noisy.stable_hash(), snapshot().stable_hash());
+    }
+
+    #[test]
+    fn perturbed_snapshots_hash_differently() {
+        let mut moved = snapshot();
+        moved.left_paddle_y += 1.;
+        assert_ne!(moved.stable_hash(), snapshot().stable_hash());
+
+        let mut scored = snapshot();
+        scored.score.1 += 1;
+        assert_ne!(scored.stable_hash(), snapshot().stable_hash());
+    }
+}
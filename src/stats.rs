@@ -0,0 +1,74 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::side::Side;
+
+/// A single point in the match timeline.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PointRecord {
+    pub scorer: Side,
+    /// Match time at which the point was scored
+    pub time_secs: f32,
+    /// Score right after the point
+    pub score: (u8, u8),
+}
+
+/// Summary of a match, accumulated during play and exported when it ends.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MatchStats {
+    pub final_score: (u8, u8),
+    pub duration_secs: f32,
+    pub longest_rally: u32,
+    pub total_paddle_hits: u32,
+    pub points: Vec<PointRecord>,
+}
+
+impl MatchStats {
+    pub fn record_paddle_hit(&mut self, rally_hits: u32) {
+        self.total_paddle_hits += 1;
+        self.longest_rally = self.longest_rally.max(rally_hits);
+    }
+
+    pub fn record_point(&mut self, scorer: Side, time_secs: f32, score: (u8, u8)) {
+        self.final_score = score;
+        self.duration_secs = time_secs;
+        self.points.push(PointRecord {
+            scorer,
+            time_secs,
+            score,
+        });
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Writes the stats as JSON to `path`.
+    pub fn export(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let json = self.to_json().map_err(std::io::Error::other)?;
+        std::fs::write(path, json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn populated_stats_round_trip_through_json() {
+        let mut stats = MatchStats::default();
+        stats.record_paddle_hit(1);
+        stats.record_paddle_hit(2);
+        stats.record_point(Side::Left, 4.5, (1, 0));
+        stats.record_paddle_hit(1);
+        stats.record_point(Side::Right, 9.25, (1, 1));
+
+        let json = stats.to_json().unwrap();
+        let parsed: MatchStats = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(parsed, stats);
+        assert_eq!(parsed.longest_rally, 2);
+        assert_eq!(parsed.total_paddle_hits, 3);
+    }
+}
@@ -0,0 +1,332 @@
+use ggez::{graphics::Color, mint::Point2};
+use serde::{Deserialize, Serialize};
+
+use crate::side::Side;
+
+/// How far shadows are offset from the object casting them
+pub const SHADOW_OFFSET: Point2<f32> = Point2 { x: 4.0, y: 4.0 };
+
+/// Color the background warms towards as a rally goes on
+pub const RALLY_HEAT_COLOR: Color = Color {
+    r: 0.35,
+    g: 0.1,
+    b: 0.0,
+    a: 1.0,
+};
+/// Rally length at which the background is as warm as it gets
+pub const RALLY_HEAT_MAX_HITS: u32 = 20;
+/// How far towards `RALLY_HEAT_COLOR` the background can shift, kept low to stay subtle
+pub const RALLY_HEAT_MAX_BLEND: f32 = 0.5;
+
+/// Color of a mercy ball, whoever last hit it
+pub const MERCY_BALL_COLOR: Color = Color {
+    r: 0.5,
+    g: 1.0,
+    b: 0.6,
+    a: 1.0,
+};
+
+/// What is drawn behind the play field.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BackgroundStyle {
+    Solid,
+    /// Faint grid lines `spacing` pixels apart
+    Grid {
+        spacing: f32,
+    },
+    /// Vertical gradient from the background color at the top to `bottom`
+    Gradient {
+        bottom: Color,
+    },
+}
+
+impl BackgroundStyle {
+    /// Every style in the order they're cycled through on the settings screen
+    pub const ALL: [BackgroundStyle; 3] = [
+        BackgroundStyle::Solid,
+        BackgroundStyle::Grid { spacing: 40.0 },
+        BackgroundStyle::Gradient {
+            bottom: Color {
+                r: 0.05,
+                g: 0.05,
+                b: 0.25,
+                a: 1.0,
+            },
+        },
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BackgroundStyle::Solid => "Solid",
+            BackgroundStyle::Grid { .. } => "Grid",
+            BackgroundStyle::Gradient { .. } => "Gradient",
+        }
+    }
+}
+
+/// Where and how the score is drawn.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScoreLayout {
+    /// Distance of the score from the top of the window
+    pub y_offset: f32,
+    pub scale: f32,
+    pub color: Color,
+    /// Each player's score is drawn in the middle of their own half, instead of "x - y" centred
+    pub split: bool,
+}
+
+impl Default for ScoreLayout {
+    fn default() -> Self {
+        ScoreLayout {
+            y_offset: 20.0,
+            scale: 24.0,
+            color: Color::WHITE,
+            split: false,
+        }
+    }
+}
+
+impl ScoreLayout {
+    /// Where the left and right scores go in a split layout, given how wide each one's text is,
+    /// so that each is centred on its half of a window `width` wide.
+    pub fn split_positions(
+        &self,
+        width: f32,
+        left_width: f32,
+        right_width: f32,
+    ) -> (Point2<f32>, Point2<f32>) {
+        let left = Point2 {
+            x: width / 4. - left_width / 2.,
+            y: self.y_offset,
+        };
+        let right = Point2 {
+            x: width * 3. / 4. - right_width / 2.,
+            y: self.y_offset,
+        };
+        (left, right)
+    }
+}
+
+/// Colors used to render the game.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    pub background: Color,
+    /// Paddles and obstacles
+    pub foreground: Color,
+    /// A ball nobody has returned yet
+    pub ball: Color,
+    /// Messages, menus and other text over the field
+    pub text: Color,
+    pub shadow: Color,
+    /// Tints for a ball last returned by the left or right paddle
+    pub accents: (Color, Color),
+    pub background_style: BackgroundStyle,
+    pub score_layout: ScoreLayout,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            background: Color::BLACK,
+            foreground: Color::WHITE,
+            ball: Color::WHITE,
+            text: Color::WHITE,
+            shadow: Color::new(1.0, 1.0, 1.0, 0.2),
+            accents: (
+                Color::new(0.5, 0.7, 1.0, 1.0),
+                Color::new(1.0, 0.55, 0.5, 1.0),
+            ),
+            background_style: BackgroundStyle::Solid,
+            score_layout: ScoreLayout::default(),
+        }
+    }
+}
+
+impl Theme {
+    /// The ball's color given who last returned it: their accent, or the ball color for a fresh serve.
+    pub fn ball_color(&self, last_hitter: Option<Side>) -> Color {
+        match last_hitter {
+            Some(side) => *side.of(&self.accents),
+            None => self.ball,
+        }
+    }
+
+    /// This theme with its background warmed up to reflect a rally of `rally_hits` paddle hits.
+    pub fn with_rally_heat(&self, rally_hits: u32) -> Theme {
+        Theme {
+            background: rally_background(self.background, rally_hits),
+            ..*self
+        }
+    }
+}
+
+/// A part of the game whose color a custom theme sets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThemeElement {
+    Background,
+    Paddle,
+    Ball,
+    Text,
+}
+
+impl ThemeElement {
+    /// Every element in the order the color picker lists them
+    pub const ALL: [ThemeElement; 4] = [
+        ThemeElement::Background,
+        ThemeElement::Paddle,
+        ThemeElement::Ball,
+        ThemeElement::Text,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ThemeElement::Background => "Background",
+            ThemeElement::Paddle => "Paddles",
+            ThemeElement::Ball => "Ball",
+            ThemeElement::Text => "Text",
+        }
+    }
+}
+
+/// Colors built on the color picker screen, as 0-255 red, green and blue per element.
+/// Saved to the config and laid over the default theme.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CustomTheme {
+    pub background: [u8; 3],
+    pub paddle: [u8; 3],
+    pub ball: [u8; 3],
+    pub text: [u8; 3],
+}
+
+impl Default for CustomTheme {
+    fn default() -> Self {
+        CustomTheme {
+            background: [0, 0, 0],
+            paddle: [255, 255, 255],
+            ball: [255, 255, 255],
+            text: [255, 255, 255],
+        }
+    }
+}
+
+impl CustomTheme {
+    pub fn rgb(&self, element: ThemeElement) -> [u8; 3] {
+        match element {
+            ThemeElement::Background => self.background,
+            ThemeElement::Paddle => self.paddle,
+            ThemeElement::Ball => self.ball,
+            ThemeElement::Text => self.text,
+        }
+    }
+
+    fn rgb_mut(&mut self, element: ThemeElement) -> &mut [u8; 3] {
+        match element {
+            ThemeElement::Background => &mut self.background,
+            ThemeElement::Paddle => &mut self.paddle,
+            ThemeElement::Ball => &mut self.ball,
+            ThemeElement::Text => &mut self.text,
+        }
+    }
+
+    /// Changes `element`'s red, green or blue (`channel` 0, 1 or 2) by `amount`, staying
+    /// within 0-255.
+    pub fn adjust(&mut self, element: ThemeElement, channel: usize, amount: i32) {
+        let value = &mut self.rgb_mut(element)[channel];
+        *value = (*value as i32 + amount).clamp(0, 255) as u8;
+    }
+
+    pub fn color(&self, element: ThemeElement) -> Color {
+        let [r, g, b] = self.rgb(element);
+        Color::from_rgb(r, g, b)
+    }
+
+    /// Sets `theme`'s colors to this custom theme's, leaving its styles alone.
+    pub fn apply(&self, theme: &mut Theme) {
+        theme.background = self.color(ThemeElement::Background);
+        theme.foreground = self.color(ThemeElement::Paddle);
+        theme.ball = self.color(ThemeElement::Ball);
+        theme.text = self.color(ThemeElement::Text);
+    }
+}
+
+/// Blends `base` towards `RALLY_HEAT_COLOR` the longer a rally gets.
+pub fn rally_background(base: Color, rally_hits: u32) -> Color {
+    let heat = rally_hits.min(RALLY_HEAT_MAX_HITS) as f32 / RALLY_HEAT_MAX_HITS as f32;
+    let t = heat * RALLY_HEAT_MAX_BLEND;
+    let lerp = |from: f32, to: f32| from + (to - from) * t;
+    Color::new(
+        lerp(base.r, RALLY_HEAT_COLOR.r),
+        lerp(base.g, RALLY_HEAT_COLOR.g),
+        lerp(base.b, RALLY_HEAT_COLOR.b),
+        base.a,
+    )
+}
+
+/// Where a shadow is drawn for an object drawn at `dest`.
+pub fn shadow_dest(dest: Point2<f32>) -> Point2<f32> {
+    Point2 {
+        x: dest.x + SHADOW_OFFSET.x,
+        y: dest.y + SHADOW_OFFSET.y,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shadow_is_offset_down_and_right() {
+        let dest = Point2 { x: 10., y: -3. };
+
+        assert_eq!(shadow_dest(dest), Point2 { x: 14., y: 1. });
+    }
+
+    #[test]
+    fn background_warms_with_the_rally_up_to_a_limit() {
+        assert_eq!(rally_background(Color::BLACK, 0), Color::BLACK);
+
+        let halfway = rally_background(Color::BLACK, RALLY_HEAT_MAX_HITS / 2);
+        assert!((halfway.r - RALLY_HEAT_COLOR.r * RALLY_HEAT_MAX_BLEND / 2.).abs() < 1e-5);
+        assert_eq!(halfway.a, 1.);
+
+        let hottest = rally_background(Color::BLACK, RALLY_HEAT_MAX_HITS);
+        assert!((hottest.r - RALLY_HEAT_COLOR.r * RALLY_HEAT_MAX_BLEND).abs() < 1e-5);
+        assert_eq!(
+            rally_background(Color::BLACK, RALLY_HEAT_MAX_HITS * 3),
+            hottest
+        );
+    }
+
+    #[test]
+    fn split_scores_are_centred_on_their_halves() {
+        let layout = ScoreLayout {
+            y_offset: 35.,
+            split: true,
+            ..ScoreLayout::default()
+        };
+
+        let (left, right) = layout.split_positions(800., 20., 40.);
+        assert_eq!(left, Point2 { x: 190., y: 35. });
+        assert_eq!(right, Point2 { x: 580., y: 35. });
+    }
+
+    #[test]
+    fn custom_colors_stay_within_the_byte_range() {
+        let mut custom = CustomTheme::default();
+        custom.adjust(ThemeElement::Background, 0, 100);
+        custom.adjust(ThemeElement::Background, 1, -20);
+        custom.adjust(ThemeElement::Paddle, 2, 40);
+        custom.adjust(ThemeElement::Paddle, 0, -300);
+        assert_eq!(custom.rgb(ThemeElement::Background), [100, 0, 0]);
+        assert_eq!(custom.rgb(ThemeElement::Paddle), [0, 255, 255]);
+        assert_eq!(
+            custom.color(ThemeElement::Background),
+            Color::from_rgb(100, 0, 0)
+        );
+
+        let mut theme = Theme::default();
+        custom.apply(&mut theme);
+        assert_eq!(theme.foreground, Color::from_rgb(0, 255, 255));
+    }
+}
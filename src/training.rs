@@ -0,0 +1,79 @@
+use ggez::graphics::Rect;
+
+/// Size of each target zone on the wall
+pub const TARGET_HEIGHT: f32 = 60.0;
+pub const TARGET_DEPTH: f32 = 12.0;
+/// Number of zones spread along the wall
+pub const TARGET_COUNT: usize = 3;
+
+/// Target zones on the right wall for solo practice, and how often shots land in them.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TrainingTargets {
+    pub targets: Vec<Rect>,
+    /// Balls that reached the wall through a target
+    pub hits: u32,
+    /// Balls that reached the wall at all
+    pub attempts: u32,
+}
+
+impl TrainingTargets {
+    /// Spreads the zones evenly along the right wall of a field of the given size.
+    pub fn layout(&mut self, width: f32, height: f32) {
+        let gap = (height - TARGET_COUNT as f32 * TARGET_HEIGHT) / (TARGET_COUNT as f32 + 1.);
+        self.targets = (0..TARGET_COUNT)
+            .map(|i| {
+                Rect::new(
+                    width - TARGET_DEPTH,
+                    gap + i as f32 * (TARGET_HEIGHT + gap),
+                    TARGET_DEPTH,
+                    TARGET_HEIGHT,
+                )
+            })
+            .collect();
+    }
+
+    /// Records a ball reaching the wall at height `ball_y`.
+    /// Returns true if it went through a target.
+    pub fn record_shot(&mut self, ball_y: f32) -> bool {
+        self.attempts += 1;
+        let on_target = self
+            .targets
+            .iter()
+            .any(|target| ball_y >= target.y && ball_y <= target.y + target.h);
+        if on_target {
+            self.hits += 1;
+        }
+        on_target
+    }
+
+    /// Share of shots on target as a percentage, once there's been a shot.
+    pub fn accuracy(&self) -> Option<f32> {
+        (self.attempts > 0).then(|| self.hits as f32 / self.attempts as f32 * 100.)
+    }
+
+    pub fn reset(&mut self) {
+        self.hits = 0;
+        self.attempts = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shots_through_a_target_count_towards_accuracy() {
+        let mut training = TrainingTargets::default();
+        training.layout(800., 600.);
+        assert_eq!(training.targets.len(), TARGET_COUNT);
+        assert_eq!(training.accuracy(), None);
+
+        let target = training.targets[1];
+        assert!(training.record_shot(target.y + target.h / 2.));
+        assert_eq!((training.hits, training.attempts), (1, 1));
+
+        assert!(!training.record_shot(0.));
+        assert_eq!((training.hits, training.attempts), (1, 2));
+        assert_eq!(training.accuracy(), Some(50.));
+    }
+}
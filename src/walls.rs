@@ -0,0 +1,158 @@
+use ggez::{graphics::Rect, mint::Point2};
+use serde::{Deserialize, Serialize};
+
+use crate::side::Side;
+
+/// What happens to a ball that reaches a wall.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WallBehavior {
+    /// It bounces back into the field
+    Bounce,
+    /// It comes back in through the opposite wall
+    Wrap,
+    /// It leaves the field and a point is scored
+    Score,
+}
+
+impl WallBehavior {
+    /// Every behavior in the order the settings screen cycles them
+    pub const ALL: [WallBehavior; 3] = [
+        WallBehavior::Bounce,
+        WallBehavior::Wrap,
+        WallBehavior::Score,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            WallBehavior::Bounce => "Bounce",
+            WallBehavior::Wrap => "Wrap",
+            WallBehavior::Score => "Score",
+        }
+    }
+}
+
+/// One of the four walls around the field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edge {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+impl Edge {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Edge::Top => "Top",
+            Edge::Bottom => "Bottom",
+            Edge::Left => "Left",
+            Edge::Right => "Right",
+        }
+    }
+
+    /// This wall's behavior in `walls`, which lists them top, bottom, left, right.
+    pub fn behavior(&self, walls: &[WallBehavior; 4]) -> WallBehavior {
+        walls[*self as usize]
+    }
+
+    pub fn behavior_mut<'a>(&self, walls: &'a mut [WallBehavior; 4]) -> &'a mut WallBehavior {
+        &mut walls[*self as usize]
+    }
+}
+
+/// Classic pong: the ball bounces off the top and bottom and scores past either paddle
+pub const CLASSIC_WALLS: [WallBehavior; 4] = [
+    WallBehavior::Bounce,
+    WallBehavior::Bounce,
+    WallBehavior::Score,
+    WallBehavior::Score,
+];
+
+/// The walls of a `width` by `height` field that `ball_box` has reached, top and bottom first.
+pub fn edges_reached(ball_box: Rect, width: f32, height: f32) -> impl Iterator<Item = Edge> {
+    [
+        (Edge::Top, ball_box.top() < 0.),
+        (Edge::Bottom, ball_box.bottom() > height),
+        (Edge::Left, ball_box.left() < 0.),
+        (Edge::Right, ball_box.right() > width),
+    ]
+    .into_iter()
+    .filter_map(|(edge, reached)| reached.then_some(edge))
+}
+
+/// Who a ball leaving through `edge` scores for. Past a paddle it's the other player; out
+/// through the top or bottom it's whoever didn't hit it out, the receiver for an untouched serve.
+pub fn scorer(edge: Edge, last_hitter: Option<Side>, server: Side) -> Side {
+    match edge {
+        Edge::Left => Side::Right,
+        Edge::Right => Side::Left,
+        Edge::Top | Edge::Bottom => last_hitter.unwrap_or(server).opposite(),
+    }
+}
+
+/// Brings a ball whose center has gone past `edge` of a `width` by `height` field back in
+/// through the opposite wall. A ball only partway past it is left alone.
+pub fn wrap(pos: &mut Point2<f32>, edge: Edge, width: f32, height: f32) {
+    match edge {
+        Edge::Top if pos.y < 0. => pos.y += height,
+        Edge::Bottom if pos.y > height => pos.y -= height,
+        Edge::Left if pos.x < 0. => pos.x += width,
+        Edge::Right if pos.x > width => pos.x -= width,
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edges_reached_lists_every_wall_the_ball_crosses() {
+        let reached =
+            |x, y| edges_reached(Rect::new(x, y, 10., 10.), 800., 600.).collect::<Vec<_>>();
+
+        assert_eq!(reached(400., 300.), []);
+        assert_eq!(reached(-1., 300.), [Edge::Left]);
+        assert_eq!(reached(791., 591.), [Edge::Bottom, Edge::Right]);
+        assert_eq!(reached(-1., -1.), [Edge::Top, Edge::Left]);
+    }
+
+    #[test]
+    fn the_top_and_bottom_score_against_whoever_hit_it_out() {
+        assert_eq!(
+            scorer(Edge::Left, Some(Side::Left), Side::Left),
+            Side::Right
+        );
+        assert_eq!(
+            scorer(Edge::Right, Some(Side::Left), Side::Left),
+            Side::Left
+        );
+        assert_eq!(
+            scorer(Edge::Top, Some(Side::Left), Side::Right),
+            Side::Right
+        );
+        // An untouched serve goes to the receiver
+        assert_eq!(scorer(Edge::Bottom, None, Side::Right), Side::Left);
+    }
+
+    #[test]
+    fn wrap_waits_for_the_center_to_leave_the_field() {
+        let mut pos = Point2 { x: 5., y: 300. };
+        wrap(&mut pos, Edge::Left, 800., 600.);
+        assert_eq!((pos.x, pos.y), (5., 300.));
+
+        let mut pos = Point2 { x: -5., y: 610. };
+        wrap(&mut pos, Edge::Left, 800., 600.);
+        wrap(&mut pos, Edge::Bottom, 800., 600.);
+        assert_eq!((pos.x, pos.y), (795., 10.));
+    }
+
+    #[test]
+    fn edges_index_their_own_behavior() {
+        let mut walls = CLASSIC_WALLS;
+        *Edge::Top.behavior_mut(&mut walls) = WallBehavior::Wrap;
+        assert_eq!(Edge::Top.behavior(&walls), WallBehavior::Wrap);
+        assert_eq!(Edge::Bottom.behavior(&walls), WallBehavior::Bounce);
+        assert_eq!(Edge::Right.behavior(&walls), WallBehavior::Score);
+    }
+}